@@ -1,6 +1,6 @@
 //! BitBoard utilities.
 
-use std::fmt::Display;
+use core::fmt::Display;
 
 use derive_more::{
     BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, ShlAssign, Shr,
@@ -63,12 +63,72 @@ impl BitBoard {
         Self(1 << square.raw_index())
     }
 
-    /// Try to resolve this bitboard into a singular square.
+    /// Resolve this bitboard into a singular square.
+    ///
+    /// Returns `None` if no squares or more than one square are set.
+    #[inline]
+    pub const fn to_square(&self) -> Option<Square> {
+        if self.0.count_ones() == 1 {
+            Some(self.to_square_unchecked())
+        } else {
+            None
+        }
+    }
+
+    /// Resolve this bitboard into a singular square, without checking that exactly one square is
+    /// set.
     #[inline]
     pub const fn to_square_unchecked(&self) -> Square {
         Square::from_index_unchecked(self.0.trailing_zeros() as u8)
     }
 
+    /// Flip the board vertically, so that rank 1 becomes rank 8.
+    #[inline]
+    pub const fn flip_vertical(&self) -> Self {
+        Self(self.0.swap_bytes())
+    }
+
+    /// Flip the board horizontally, so that the a-file becomes the h-file.
+    #[inline]
+    pub const fn flip_horizontal(&self) -> Self {
+        const K1: u64 = 0x55_55_55_55_55_55_55_55;
+        const K2: u64 = 0x33_33_33_33_33_33_33_33;
+        const K4: u64 = 0x0f_0f_0f_0f_0f_0f_0f_0f;
+
+        let x = self.0;
+        let x = ((x >> 1) & K1) | ((x & K1) << 1);
+        let x = ((x >> 2) & K2) | ((x & K2) << 2);
+        let x = ((x >> 4) & K4) | ((x & K4) << 4);
+        Self(x)
+    }
+
+    /// Flip the board about the a1-h8 diagonal, so that the a-file becomes rank 1.
+    ///
+    /// <https://www.chessprogramming.org/Flipping_Mirroring_and_Rotating#FlipabouttheDiagonal>
+    #[inline]
+    pub const fn flip_diagonal(&self) -> Self {
+        const K1: u64 = 0x55_00_55_00_55_00_55_00;
+        const K2: u64 = 0x33_33_00_00_33_33_00_00;
+        const K4: u64 = 0x0f_0f_0f_0f_00_00_00_00;
+
+        let mut x = self.0;
+        let mut t = K4 & (x ^ (x << 28));
+        x ^= t ^ (t >> 28);
+        t = K2 & (x ^ (x << 14));
+        x ^= t ^ (t >> 14);
+        t = K1 & (x ^ (x << 7));
+        x ^= t ^ (t >> 7);
+        Self(x)
+    }
+
+    /// The board from the other side's perspective, i.e, flipped vertically.
+    ///
+    /// See [`Board::mirror`](crate::Board::mirror).
+    #[inline]
+    pub const fn mirror(&self) -> Self {
+        self.flip_vertical()
+    }
+
     /// An iterator over all `set` squares on the board.
     #[inline]
     pub const fn set_iter(&self) -> SetIter {
@@ -84,7 +144,7 @@ impl PartialEq<u64> for BitBoard {
 }
 
 impl Display for BitBoard {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let mut square = Square::at(7, 0).unwrap();
 
         for _ in 0..8 {
@@ -111,17 +171,17 @@ pub struct SetIter {
 impl Iterator for SetIter {
     type Item = Square;
 
+    #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        let trailing = self.inner.0.trailing_zeros() as u8;
-
-        if trailing < 64 {
-            let sq = Square::from_index_unchecked(trailing);
-            let sq_bb = BitBoard::from_square(sq);
-            self.inner &= !sq_bb;
-            return Some(sq);
+        if self.inner.0 == 0 {
+            return None;
         }
 
-        None
+        // isolate and clear the least significant bit
+        let lsb = self.inner.0 & self.inner.0.wrapping_neg();
+        self.inner.0 ^= lsb;
+
+        Some(Square::from_index_unchecked(lsb.trailing_zeros() as u8))
     }
 
     #[inline]
@@ -142,5 +202,196 @@ pub mod constants {
     use super::*;
 
     pub const A_FILE: BitBoard = BitBoard(0x01_01_01_01_01_01_01_01);
+    pub const B_FILE: BitBoard = BitBoard(A_FILE.0 << 1);
+    pub const C_FILE: BitBoard = BitBoard(A_FILE.0 << 2);
+    pub const D_FILE: BitBoard = BitBoard(A_FILE.0 << 3);
+    pub const E_FILE: BitBoard = BitBoard(A_FILE.0 << 4);
+    pub const F_FILE: BitBoard = BitBoard(A_FILE.0 << 5);
+    pub const G_FILE: BitBoard = BitBoard(A_FILE.0 << 6);
     pub const H_FILE: BitBoard = BitBoard(0x80_80_80_80_80_80_80_80);
+
+    pub const RANK_1: BitBoard = BitBoard(0xff);
+
+    /// All files, indexed by [`Square::file`].
+    pub const FILES: [BitBoard; 8] = [
+        A_FILE, B_FILE, C_FILE, D_FILE, E_FILE, F_FILE, G_FILE, H_FILE,
+    ];
+
+    /// All ranks, indexed by [`Square::rank`].
+    pub const RANKS: [BitBoard; 8] = {
+        let mut ranks = [BitBoard::ZERO; 8];
+        let mut i = 0;
+
+        while i < 8 {
+            ranks[i] = BitBoard(RANK_1.0 << (i * 8));
+            i += 1;
+        }
+
+        ranks
+    };
+
+    /// The a1-h8 diagonal.
+    pub const MAIN_DIAGONAL: BitBoard = BitBoard(0x80_40_20_10_08_04_02_01);
+    /// The h1-a8 anti-diagonal.
+    pub const MAIN_ANTI_DIAGONAL: BitBoard = BitBoard(0x01_02_04_08_10_20_40_80);
+
+    /// All squares on the given file (`0..8`).
+    #[inline]
+    pub const fn file_mask(file: u8) -> BitBoard {
+        FILES[file as usize]
+    }
+
+    /// All squares on the given rank (`0..8`).
+    #[inline]
+    pub const fn rank_mask(rank: u8) -> BitBoard {
+        RANKS[rank as usize]
+    }
+
+    /// All squares on the diagonal (parallel to a1-h8) through `square`.
+    pub const fn diagonal_mask(square: Square) -> BitBoard {
+        let diff = square.file() as i32 - square.rank() as i32;
+
+        if diff >= 0 {
+            // shifting down a rank moves the diagonal right, dropping the squares that wrap
+            BitBoard(MAIN_DIAGONAL.0 >> (diff * 8))
+        } else {
+            BitBoard(MAIN_DIAGONAL.0 << (-diff * 8))
+        }
+    }
+
+    /// All squares on the anti-diagonal (parallel to h1-a8) through `square`.
+    pub const fn anti_diagonal_mask(square: Square) -> BitBoard {
+        let diff = square.file() as i32 + square.rank() as i32 - 7;
+
+        if diff >= 0 {
+            BitBoard(MAIN_ANTI_DIAGONAL.0 << (diff * 8))
+        } else {
+            BitBoard(MAIN_ANTI_DIAGONAL.0 >> (-diff * 8))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::constants::*;
+    use super::*;
+
+    /// An asymmetric pattern to check flips against.
+    const PATTERN: BitBoard = BitBoard(0x00_00_00_00_00_04_0b_01);
+
+    #[test]
+    fn flip() {
+        assert_eq!(A_FILE.flip_horizontal(), H_FILE);
+        assert_eq!(H_FILE.flip_horizontal(), A_FILE);
+        assert_eq!(A_FILE.flip_vertical(), A_FILE);
+        assert_eq!(A_FILE.flip_diagonal(), BitBoard(0xff));
+        assert_eq!(BitBoard(0xff).mirror(), BitBoard(0xff << 56));
+
+        let sq = |rank, file| BitBoard::from_square(Square::at(rank, file).unwrap());
+        assert_eq!(sq(1, 2).flip_vertical(), sq(6, 2));
+        assert_eq!(sq(1, 2).flip_horizontal(), sq(1, 5));
+        assert_eq!(sq(1, 2).flip_diagonal(), sq(2, 1));
+    }
+
+    #[test]
+    fn double_flip() {
+        assert_eq!(PATTERN.flip_vertical().flip_vertical(), PATTERN);
+        assert_eq!(PATTERN.flip_horizontal().flip_horizontal(), PATTERN);
+        assert_eq!(PATTERN.flip_diagonal().flip_diagonal(), PATTERN);
+        assert_eq!(PATTERN.mirror().mirror(), PATTERN);
+
+        assert_ne!(PATTERN.flip_vertical(), PATTERN);
+        assert_ne!(PATTERN.flip_horizontal(), PATTERN);
+        assert_ne!(PATTERN.flip_diagonal(), PATTERN);
+    }
+
+    #[test]
+    fn files_and_ranks() {
+        assert_eq!(FILES[0], A_FILE);
+        assert_eq!(FILES[7], H_FILE);
+        assert_eq!(RANKS[0], RANK_1);
+
+        let full = BitBoard(u64::MAX);
+        assert_eq!(FILES.into_iter().fold(BitBoard::ZERO, |a, b| a | b), full);
+        assert_eq!(RANKS.into_iter().fold(BitBoard::ZERO, |a, b| a | b), full);
+
+        let e4 = Square::at(3, 4).unwrap();
+        assert_eq!(
+            file_mask(e4.file()) & rank_mask(e4.rank()),
+            BitBoard::from_square(e4)
+        );
+    }
+
+    #[test]
+    fn diagonals() {
+        let sq = |rank, file| Square::at(rank, file).unwrap();
+
+        assert_eq!(diagonal_mask(sq(0, 0)), MAIN_DIAGONAL);
+        assert_eq!(diagonal_mask(sq(4, 4)), MAIN_DIAGONAL);
+        assert_eq!(anti_diagonal_mask(sq(0, 7)), MAIN_ANTI_DIAGONAL);
+        assert_eq!(anti_diagonal_mask(sq(3, 4)), MAIN_ANTI_DIAGONAL);
+
+        // b1-h7
+        assert_eq!(diagonal_mask(sq(3, 4)), BitBoard(0x00_80_40_20_10_08_04_02));
+        // a2-g8
+        assert_eq!(diagonal_mask(sq(4, 3)), BitBoard(0x40_20_10_08_04_02_01_00));
+        // corners are on a diagonal of their own
+        assert_eq!(diagonal_mask(sq(0, 7)), BitBoard::from_square(sq(0, 7)));
+        assert_eq!(
+            anti_diagonal_mask(sq(0, 0)),
+            BitBoard::from_square(sq(0, 0))
+        );
+        assert_eq!(
+            anti_diagonal_mask(sq(7, 7)),
+            BitBoard::from_square(sq(7, 7))
+        );
+
+        for square in (0..64).map(Square::from_index_unchecked) {
+            let bb = BitBoard::from_square(square);
+            assert_eq!(diagonal_mask(square) & anti_diagonal_mask(square), bb);
+            assert_eq!(
+                diagonal_mask(square).flip_horizontal(),
+                anti_diagonal_mask(Square::at(square.rank(), 7 - square.file()).unwrap())
+            );
+        }
+    }
+
+    #[test]
+    fn to_square() {
+        let e4 = Square::at(3, 4).unwrap();
+
+        assert_eq!(BitBoard::ZERO.to_square(), None);
+        assert_eq!(BitBoard::from_square(e4).to_square(), Some(e4));
+        assert_eq!(
+            (BitBoard::from_square(e4) | BitBoard::from_square(Square::at(0, 0).unwrap()))
+                .to_square(),
+            None
+        );
+    }
+
+    #[test]
+    fn set_iter() {
+        // ascending square order, same as testing each square in turn
+        for bb in [
+            BitBoard::ZERO,
+            PATTERN,
+            A_FILE | rank_mask(7),
+            BitBoard(u64::MAX),
+        ] {
+            let expected: Vec<_> = (0..64)
+                .map(Square::from_index_unchecked)
+                .filter(|square| bb.get(*square))
+                .collect();
+            let squares: Vec<_> = bb.set_iter().collect();
+
+            assert_eq!(squares, expected);
+            assert_eq!(bb.set_iter().len(), expected.len());
+        }
+
+        let mut iter = BitBoard(1 << 63 | 1).set_iter();
+        assert_eq!(iter.next(), Some(Square::from_index_unchecked(0)));
+        assert_eq!(iter.len(), 1);
+        assert_eq!(iter.next(), Some(Square::from_index_unchecked(63)));
+        assert_eq!(iter.next(), None);
+    }
 }
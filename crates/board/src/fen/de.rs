@@ -0,0 +1,334 @@
+//! Fen parser implementation.
+//!
+//! <https://en.wikipedia.org/wiki/Forsyth%E2%80%93Edwards_Notation>
+
+use std::str::FromStr;
+
+use nom::bytes::complete::{is_not, take_till};
+use nom::character::complete::{digit1, one_of, space0, space1};
+use nom::character::{is_digit, is_space};
+use nom::combinator::{map_res, opt};
+use nom::error::{Error, ErrorKind};
+use nom::multi::{many0, many1};
+use nom::sequence::{preceded, Tuple};
+use nom::IResult;
+
+use crate::{Board, CastlingRights, Color, Piece, PieceKind, Position, Square};
+
+/// Parsing options.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParseOptions {
+    /// Only accept the lowercase active color and en passant target required by the spec.
+    ///
+    /// Otherwise, `W`/`B` and uppercase squares like `E3`, which some generators emit, are
+    /// accepted as well.
+    pub strict: bool,
+}
+
+fn parse_board(mut input: &str) -> IResult<&str, Board> {
+    let mut board = Board::default();
+    let mut square = Square::at(7, 0).unwrap();
+
+    for _ in 0..8 {
+        let (new_input, rank) = take_till(|c| c == '/' || is_space(c as u8))(input)?;
+        let (new_input, _) = many0(one_of("/"))(new_input)?;
+
+        for char in rank.chars() {
+            // FIXME: add some checks to increment square index
+            if is_digit(char as u8) {
+                *square.raw_index_mut() += (char as u8).saturating_sub(b'0');
+                continue;
+            }
+
+            board.set(square, Piece::from_char(char));
+            *square.raw_index_mut() += 1;
+        }
+
+        input = new_input;
+        *square.raw_index_mut() = square.raw_index().saturating_sub(16);
+    }
+
+    Ok((input, board))
+}
+
+fn parse_active_color(input: &str, options: ParseOptions) -> IResult<&str, Color> {
+    let accepted = if options.strict { "wb" } else { "wbWB" };
+    let (input, active_color) = one_of(accepted)(input)?;
+
+    let active_color = match active_color {
+        'w' | 'W' => Color::White,
+        'b' | 'B' => Color::Black,
+        _ => unreachable!(),
+    };
+
+    Ok((input, active_color))
+}
+
+/// Side of a Shredder castling file letter, `true` for kingside.
+///
+/// Only standard castling is supported, so the king has to be on its starting square and the
+/// letter has to name a corner holding a rook of `color`.
+fn shredder_side(board: &Board, color: Color, file: u8) -> Option<bool> {
+    let back_rank = match color {
+        Color::White => 0,
+        Color::Black => 7,
+    };
+
+    if board.find_king(color) != Square::at(back_rank, 4) {
+        return None;
+    }
+
+    let rook = Piece {
+        color,
+        kind: PieceKind::Rook,
+    };
+    if board.get(Square::at(back_rank, file)?) != Some(rook) {
+        return None;
+    }
+
+    match file {
+        0 => Some(false),
+        7 => Some(true),
+        _ => None,
+    }
+}
+
+/// Parse castling rights in standard (`KQkq`) or Shredder (`HAha`) notation.
+///
+/// Shredder notation names the file of the castling rook instead. Chess960 positions aren't
+/// supported, so it is only accepted for the standard king and rook files.
+fn parse_castling_rights<'a>(input: &'a str, board: &Board) -> IResult<&'a str, CastlingRights> {
+    let (rest, castle_str) = many1(one_of("KQkqABCDEFGHabcdefgh-"))(input)?;
+    let mut castling_rights = CastlingRights::empty();
+    let unsupported = || nom::Err::Error(Error::new(input, ErrorKind::Verify));
+
+    for c in castle_str {
+        let (color, kingside) = match c {
+            'K' => (Color::White, true),
+            'Q' => (Color::White, false),
+            'k' => (Color::Black, true),
+            'q' => (Color::Black, false),
+            'A'..='H' => (
+                Color::White,
+                shredder_side(board, Color::White, c as u8 - b'A').ok_or_else(unsupported)?,
+            ),
+            'a'..='h' => (
+                Color::Black,
+                shredder_side(board, Color::Black, c as u8 - b'a').ok_or_else(unsupported)?,
+            ),
+            _ => continue,
+        };
+
+        castling_rights |= if kingside {
+            CastlingRights::kingside(color)
+        } else {
+            CastlingRights::queenside(color)
+        };
+    }
+
+    Ok((rest, castling_rights))
+}
+
+/// Parse the en passant target square, which must be on the 3rd/6th rank for the side that
+/// just moved, i.e, the opponent of `active_color`.
+fn parse_ep_target(
+    input: &str,
+    active_color: Color,
+    options: ParseOptions,
+) -> IResult<&str, Option<Square>> {
+    let (rest, ep_target) = is_not(" \t\r\n")(input)?;
+
+    if ep_target == "-" {
+        return Ok((rest, None));
+    }
+    if options.strict && ep_target.bytes().any(|c| c.is_ascii_uppercase()) {
+        return Err(nom::Err::Error(Error::new(input, ErrorKind::Verify)));
+    }
+
+    let rank = match active_color {
+        Color::White => 5,
+        Color::Black => 2,
+    };
+
+    match Square::from_str(ep_target) {
+        Ok(square) if square.rank() == rank => Ok((rest, Some(square))),
+        _ => Err(nom::Err::Error(Error::new(input, ErrorKind::Verify))),
+    }
+}
+
+fn parse_u8(input: &str) -> IResult<&str, u8> {
+    map_res(digit1, str::parse)(input)
+}
+
+fn parse_u16(input: &str) -> IResult<&str, u16> {
+    map_res(digit1, str::parse)(input)
+}
+
+/// Parse a chessboard state from the provided FEN string.
+#[inline]
+pub fn parse(input: &str) -> IResult<&str, Position> {
+    parse_with(input, ParseOptions::default())
+}
+
+/// Parse a chessboard state from the provided FEN string, see [`ParseOptions`].
+pub fn parse_with(input: &str, options: ParseOptions) -> IResult<&str, Position> {
+    let (input, (_, board, _)) = (space0, parse_board, space1).parse(input)?;
+    let (input, active_color) = parse_active_color(input, options)?;
+    let (input, _) = space1(input)?;
+
+    let (input, castling) = parse_castling_rights(input, &board)?;
+    let (input, _) = space1(input)?;
+
+    let (input, ep_target) = parse_ep_target(input, active_color, options)?;
+
+    let (input, (halfmove_clock, fullmove_counter)) = (
+        // clocks are commonly left out
+        opt(preceded(space1, parse_u8)),
+        opt(preceded(space1, parse_u16)),
+    )
+        .parse(input)?;
+
+    let halfmove_clock = halfmove_clock.unwrap_or(0);
+    let fullmove_counter = fullmove_counter.unwrap_or(1);
+
+    Ok((
+        input,
+        Position {
+            board,
+            active_color,
+            castling,
+            ep_target,
+            halfmove_clock,
+            fullmove_counter,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn starting_pos() {
+        let position = parse("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+            .unwrap()
+            .1;
+
+        assert_eq!(
+            position,
+            Position {
+                board: Board::starting_position(),
+                active_color: Color::White,
+                castling: CastlingRights::all(),
+                ep_target: None,
+                halfmove_clock: 0,
+                fullmove_counter: 1,
+            }
+        )
+    }
+
+    #[test]
+    fn missing_clocks() {
+        let expected = parse("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1")
+            .unwrap()
+            .1;
+
+        let position = parse("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3")
+            .unwrap()
+            .1;
+        assert_eq!(position, expected);
+
+        // trailing whitespace after the last field
+        let position = parse("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 ")
+            .unwrap()
+            .1;
+        assert_eq!(position, expected);
+
+        let position = parse("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 7 42").unwrap().1;
+        assert_eq!(position.halfmove_clock, 7);
+        assert_eq!(position.fullmove_counter, 42);
+    }
+
+    #[test]
+    fn shredder_castling() {
+        let castling = |fen| parse(fen).unwrap().1.castling;
+
+        assert_eq!(
+            castling("r3k2r/8/8/8/8/8/8/R3K2R w HAha - 0 1"),
+            CastlingRights::all()
+        );
+        assert_eq!(
+            castling("r3k2r/8/8/8/8/8/8/R3K2R w Ah - 0 1"),
+            CastlingRights::WHITE_OOO | CastlingRights::BLACK_OO
+        );
+        // chess960 rook and king files aren't supported
+        assert!(parse("1r4kr/8/8/8/8/8/8/1R4KR w Hb - 0 1").is_err());
+        assert!(parse("1r1k3r/8/8/8/8/8/8/1R1K3R w HBhb - 0 1").is_err());
+        // king not on its back rank
+        assert!(parse("r6r/4k3/8/8/8/8/8/R3K2R w HAha - 0 1").is_err());
+        // no rook of the right color on the named corner
+        assert!(parse("r3k3/8/8/8/8/8/8/R3K2R w HAha - 0 1").is_err());
+        assert!(parse("r3k2R/8/8/8/8/8/8/R3K2R w HAh - 0 1").is_err());
+        assert_eq!(
+            castling("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1"),
+            CastlingRights::all()
+        );
+        assert_eq!(
+            castling("r3k2r/8/8/8/8/8/8/R3K2R w - - 0 1"),
+            CastlingRights::empty()
+        );
+
+        assert!(parse("r3k2r/8/8/8/8/8/8/R3K2R w KX - 0 1").is_err());
+    }
+
+    #[test]
+    fn ep_target() {
+        let position = parse("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3")
+            .unwrap()
+            .1;
+        assert_eq!(position.ep_target, Some(Square::from_str("d6").unwrap()));
+
+        // on the side to move's own side of the board
+        assert!(parse("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d3 0 3").is_err());
+        assert!(parse("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e6 0 1").is_err());
+        // not next to the pushed pawn's rank
+        assert!(parse("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e4 0 1").is_err());
+
+        for ep in ["e9", "x3", "e", "e3e"] {
+            let fen = format!("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq {ep} 0 1");
+            assert!(parse(&fen).is_err(), "{fen}");
+        }
+    }
+
+    #[test]
+    fn case() {
+        const FENS: [&str; 4] = [
+            "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3",
+            "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR W KQkq d6 0 3",
+            "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq D6 0 3",
+            "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR W KQkq D6 0 3",
+        ];
+        let strict = ParseOptions { strict: true };
+
+        let expected = parse_with(FENS[0], strict).unwrap().1;
+        for fen in FENS {
+            assert_eq!(parse(fen).unwrap().1, expected, "{fen}");
+        }
+        for fen in &FENS[1..] {
+            assert!(parse_with(fen, strict).is_err(), "{fen}");
+        }
+
+        let black = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR B KQkq E3 0 1";
+        let position = parse(black).unwrap().1;
+        assert_eq!(position.active_color, Color::Black);
+        assert_eq!(position.ep_target, Some(Square::from_str("e3").unwrap()));
+        assert!(parse_with(black, strict).is_err());
+    }
+
+    #[test]
+    fn long_game() {
+        let position = parse("8/8/4k3/8/8/4K3/8/8 w - - 12 500").unwrap().1;
+        assert_eq!(position.halfmove_clock, 12);
+        assert_eq!(position.fullmove_counter, 500);
+    }
+}
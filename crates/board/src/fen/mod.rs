@@ -0,0 +1,147 @@
+//! Fen de/serialization utilities.
+
+use core::fmt::Display;
+
+use crate::Position;
+
+pub mod de;
+pub mod ser;
+
+pub use de::ParseOptions;
+
+/// An error encountered while parsing a fen string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FenError {
+    /// The input is not valid fen syntax, starting at this byte offset.
+    Syntax { offset: usize },
+    /// A valid fen is followed by unexpected input, starting at this byte offset.
+    TrailingInput { offset: usize },
+}
+
+impl FenError {
+    fn from_nom(input: &str, err: nom::Err<nom::error::Error<&str>>) -> Self {
+        let offset = match err {
+            nom::Err::Error(e) | nom::Err::Failure(e) => input.len() - e.input.len(),
+            nom::Err::Incomplete(_) => input.len(),
+        };
+
+        Self::Syntax { offset }
+    }
+}
+
+impl Display for FenError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Syntax { offset } => write!(f, "invalid fen syntax at offset {offset}"),
+            Self::TrailingInput { offset } => {
+                write!(f, "unexpected input after fen at offset {offset}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+/// Parse a position from the given fen string.
+///
+/// Only whitespace may follow the fen.
+#[inline]
+pub fn from_str(s: &str) -> Result<Position, FenError> {
+    from_str_with(s, ParseOptions::default())
+}
+
+/// Parse a position from the given fen string, like [`from_str`], with the given options.
+pub fn from_str_with(s: &str, options: ParseOptions) -> Result<Position, FenError> {
+    let (rest, position) = de::parse_with(s, options).map_err(|e| FenError::from_nom(s, e))?;
+
+    let rest = rest.trim_start();
+    if !rest.is_empty() {
+        return Err(FenError::TrailingInput {
+            offset: s.len() - rest.len(),
+        });
+    }
+
+    Ok(position)
+}
+
+/// Parse one position per line, e.g. from a test suite file.
+///
+/// Blank lines and lines starting with `#` are skipped. Each line is parsed on its own, so a
+/// malformed line only results in an error for that entry. Anything following the position is
+/// ignored, like EPD operations.
+#[inline]
+pub fn parse_many(input: &str) -> Vec<Result<Position, FenError>> {
+    parse_many_with(input, ParseOptions::default())
+}
+
+/// Parse one position per line, like [`parse_many`], with the given options.
+pub fn parse_many_with(input: &str, options: ParseOptions) -> Vec<Result<Position, FenError>> {
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            de::parse_with(line, options)
+                .map(|r| r.1)
+                .map_err(|e| FenError::from_nom(line, e))
+        })
+        .collect()
+}
+
+/// Serialize a position into a fen string.
+#[inline]
+pub fn to_string(position: &Position) -> String {
+    ser::serialize(position, ser::Options::default())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn trailing_input() {
+        const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+        assert_eq!(from_str(&format!("{START}  \n")), Ok(Position::starting()));
+        assert_eq!(
+            from_str(&format!("{START} garbage")),
+            Err(FenError::TrailingInput {
+                offset: START.len() + 1
+            })
+        );
+        assert!(from_str(&format!("{START} 0")).is_err());
+        assert!(from_str("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - e4").is_err());
+    }
+
+    #[test]
+    fn parse_many() {
+        let input = "
+            # starting position
+            rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1
+            rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1
+
+            8/8/4k3/8/8/4K3/8/8 b - - 12 40
+            4k3/8/8/8/8/8/4P3/4K3 w - - bm e4; id \"push\";
+        ";
+
+        let positions = super::parse_many(input);
+        assert_eq!(positions.len(), 4);
+
+        assert_eq!(positions[0], Ok(Position::starting()));
+        assert!(matches!(positions[1], Err(FenError::Syntax { offset: 44 })));
+
+        let position = positions[2].as_ref().unwrap();
+        assert_eq!(position.halfmove_clock, 12);
+        assert_eq!(position.fullmove_counter, 40);
+
+        // epd operations are ignored
+        assert!(positions[3].is_ok());
+
+        let strict = parse_many_with(
+            "8/8/4k3/8/8/4K3/8/8 b - - 0 1\n8/8/4k3/8/8/4K3/8/8 B - - 0 1",
+            ParseOptions { strict: true },
+        );
+        assert!(strict[0].is_ok());
+        assert!(strict[1].is_err());
+    }
+}
@@ -0,0 +1,141 @@
+//! Fen serializer implementation.
+//!
+//! <https://en.wikipedia.org/wiki/Forsyth%E2%80%93Edwards_Notation>
+
+use std::fmt::Write;
+
+use crate::{Board, CastlingRights, Color, Position, Square};
+
+/// Serialization options.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    /// Only write the en passant target if it can actually be captured.
+    ///
+    /// See [`Position::normalized_ep`].
+    pub normalize_ep: bool,
+}
+
+fn write_board(output: &mut String, board: &Board) {
+    for rank in (0..8).rev() {
+        let mut empty = 0;
+
+        for file in 0..8 {
+            match board.get(Square::at(rank, file).unwrap()) {
+                Some(piece) => {
+                    if empty > 0 {
+                        output.push((b'0' + empty) as char);
+                        empty = 0;
+                    }
+                    output.push(piece.as_char());
+                }
+                None => empty += 1,
+            }
+        }
+
+        if empty > 0 {
+            output.push((b'0' + empty) as char);
+        }
+        if rank > 0 {
+            output.push('/');
+        }
+    }
+}
+
+fn write_active_color(output: &mut String, active_color: Color) {
+    output.push(match active_color {
+        Color::White => 'w',
+        Color::Black => 'b',
+    });
+}
+
+fn write_castling_rights(output: &mut String, castling: CastlingRights) {
+    if castling.is_empty() {
+        output.push('-');
+        return;
+    }
+
+    for (flag, c) in [
+        (CastlingRights::WHITE_OO, 'K'),
+        (CastlingRights::WHITE_OOO, 'Q'),
+        (CastlingRights::BLACK_OO, 'k'),
+        (CastlingRights::BLACK_OOO, 'q'),
+    ] {
+        if castling.contains(flag) {
+            output.push(c);
+        }
+    }
+}
+
+fn write_ep_target(output: &mut String, ep_target: Option<Square>) {
+    match ep_target {
+        Some(square) => write!(output, "{square}").unwrap(),
+        None => output.push('-'),
+    }
+}
+
+/// Serialize a chessboard state into a FEN string.
+pub fn serialize(position: &Position, options: Options) -> String {
+    let mut output = String::with_capacity(90);
+
+    let ep_target = if options.normalize_ep {
+        position.normalized_ep()
+    } else {
+        position.ep_target
+    };
+
+    write_board(&mut output, &position.board);
+    output.push(' ');
+    write_active_color(&mut output, position.active_color);
+    output.push(' ');
+    write_castling_rights(&mut output, position.castling);
+    output.push(' ');
+    write_ep_target(&mut output, ep_target);
+    write!(
+        output,
+        " {} {}",
+        position.halfmove_clock, position.fullmove_counter
+    )
+    .unwrap();
+
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fen::de::parse;
+
+    const FENS: [&str; 4] = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "rnbqkbnr/pppp1ppp/8/3Pp3/8/8/PPP1PPPP/RNBQKBNR w KQkq e6 0 3",
+    ];
+
+    #[test]
+    fn round_trip() {
+        for fen in FENS {
+            let position = parse(fen).unwrap().1;
+            assert_eq!(serialize(&position, Options::default()), fen);
+        }
+    }
+
+    #[test]
+    fn normalized_ep() {
+        let options = Options { normalize_ep: true };
+
+        // no black pawn can capture on e3
+        let position = parse("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1")
+            .unwrap()
+            .1;
+        assert_eq!(
+            serialize(&position, options),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"
+        );
+
+        // white pawn on d5 can capture on e6
+        let fen = "rnbqkbnr/pppp1ppp/8/3Pp3/8/8/PPP1PPPP/RNBQKBNR w KQkq e6 0 3";
+        let position = parse(fen).unwrap().1;
+        assert_eq!(serialize(&position, options), fen);
+    }
+}
@@ -2,16 +2,24 @@
 //!
 //! Only defines structures that represent the board, does not check the legality of positions
 //! or handle move generation.
+//!
+//! This crate is `no_std` compatible when the default `std` feature is disabled.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
-use std::fmt::Display;
-use std::str::FromStr;
+use core::fmt::Display;
+use core::str::FromStr;
 
 pub use strum::{EnumCount, IntoEnumIterator};
 
 pub mod bitboard;
+#[cfg(feature = "fen")]
+pub mod fen;
 pub mod moves;
 pub mod piece;
 pub mod position;
+#[cfg(feature = "serde")]
+mod serde;
 
 pub use bitboard::*;
 pub use moves::*;
@@ -46,6 +54,58 @@ impl Square {
         self.0 % 8
     }
 
+    /// Check if this is a light square.
+    #[inline]
+    pub const fn is_light(&self) -> bool {
+        (self.rank() + self.file()) % 2 == 1
+    }
+
+    /// Check if this is a dark square.
+    #[inline]
+    pub const fn is_dark(&self) -> bool {
+        !self.is_light()
+    }
+
+    /// Number of king moves between two squares (Chebyshev distance).
+    #[inline]
+    pub const fn distance(&self, other: Square) -> u8 {
+        let ranks = self.rank().abs_diff(other.rank());
+        let files = self.file().abs_diff(other.file());
+
+        if ranks > files {
+            ranks
+        } else {
+            files
+        }
+    }
+
+    /// Sum of the rank and file differences between two squares.
+    #[inline]
+    pub const fn manhattan_distance(&self, other: Square) -> u8 {
+        self.rank().abs_diff(other.rank()) + self.file().abs_diff(other.file())
+    }
+
+    /// The up to 8 squares surrounding this one, i.e, the squares a king could move to.
+    #[inline]
+    pub const fn neighbors(&self) -> BitBoard {
+        let square = BitBoard::from_square(*self).0;
+        let row = square
+            | (square << 1 & !bitboard::constants::A_FILE.0)
+            | (square >> 1 & !bitboard::constants::H_FILE.0);
+
+        BitBoard((row | row << 8 | row >> 8) & !square)
+    }
+
+    /// All squares on the files next to this square.
+    #[inline]
+    pub const fn file_neighbors(&self) -> BitBoard {
+        let file = bitboard::constants::file_mask(self.file()).0;
+        BitBoard(
+            (file << 1 & !bitboard::constants::A_FILE.0)
+                | (file >> 1 & !bitboard::constants::H_FILE.0),
+        )
+    }
+
     /// Get the internal index representation of this square.
     #[inline]
     pub const fn raw_index(&self) -> u8 {
@@ -100,7 +160,7 @@ impl FromStr for Square {
 
 impl Display for Square {
     /// Format the square into algebraic notation.
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}{}", (self.file() + b'a') as char, self.rank() + 1)
     }
 }
@@ -135,8 +195,21 @@ impl Board {
 
     /// Get the bitboard associated with a certain piece.
     #[inline]
-    pub fn get_piece_bb(&self, piece: Piece) -> BitBoard {
-        self.piece_bb[piece.kind as u8 as usize] & self.color_bb[piece.color as u8 as usize]
+    pub const fn get_piece_bb(&self, piece: Piece) -> BitBoard {
+        BitBoard(
+            self.piece_bb[piece.kind as u8 as usize].0
+                & self.color_bb[piece.color as u8 as usize].0,
+        )
+    }
+
+    /// Find the king of `color`, if there is exactly one.
+    #[inline]
+    pub fn find_king(&self, color: Color) -> Option<Square> {
+        self.get_piece_bb(Piece {
+            color,
+            kind: PieceKind::King,
+        })
+        .to_square()
     }
 
     /// Get the bitboard associated with a certain piece kind.
@@ -165,8 +238,8 @@ impl Board {
 
     /// Get the full board.
     #[inline]
-    pub fn get_full_bb(&self) -> BitBoard {
-        self.color_bb[0] | self.color_bb[1]
+    pub const fn get_full_bb(&self) -> BitBoard {
+        BitBoard(self.color_bb[0].0 | self.color_bb[1].0)
     }
 
     /// Set a piece on the board.
@@ -188,6 +261,62 @@ impl Board {
         }
     }
 
+    /// Set multiple pieces on the board, replacing any existing pieces on those squares.
+    pub fn set_many(&mut self, pieces: impl IntoIterator<Item = (Square, Piece)>) {
+        for (square, piece) in pieces {
+            self.set(square, None);
+            self.set(square, Some(piece));
+        }
+    }
+
+    /// Build a board from the piece on each square, indexed by [`Square::raw_index`].
+    ///
+    /// Useful for custom or handicap setups, without going through FEN.
+    pub fn from_placement(placement: &[Option<Piece>; 64]) -> Self {
+        let mut this = Self::default();
+
+        for (index, piece) in placement.iter().enumerate() {
+            if piece.is_some() {
+                this.set(Square::from_index_unchecked(index as u8), *piece);
+            }
+        }
+
+        this
+    }
+
+    /// The piece on each square, indexed by [`Square::raw_index`].
+    pub fn placement(&self) -> [Option<Piece>; 64] {
+        let mut placement = [None; 64];
+
+        for square in self.get_full_bb().set_iter() {
+            placement[square.raw_index() as usize] = self.get(square);
+        }
+
+        placement
+    }
+
+    /// Remove all pieces from the board.
+    #[inline]
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Check if there are no pieces on the board.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.get_full_bb().is_empty()
+    }
+
+    /// Flip the board vertically and swap the colors of all pieces.
+    ///
+    /// The resulting board is the same position from the other side's perspective.
+    pub fn mirror(&self) -> Self {
+        Self {
+            color_bb: [self.color_bb[1].mirror(), self.color_bb[0].mirror()],
+            piece_bb: self.piece_bb.map(|bb| bb.mirror()),
+        }
+    }
+
     /// Generate the starting board position.
     #[rustfmt::skip]
     pub const fn starting_position() -> Self {
@@ -212,7 +341,7 @@ impl Board {
 
 impl Display for Board {
     // not pretty but works
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         writeln!(f, " a  b  c  d  e  f  g  h")?;
         let mut square = Square::at(7, 0).unwrap();
 
@@ -260,4 +389,154 @@ mod square_tests {
         assert!(Square::from_str("b891").is_err());
         assert!(Square::from_str("b0").is_err());
     }
+
+    #[test]
+    fn neighbors() {
+        use bitboard::constants::*;
+
+        let square = |s: &str| Square::from_str(s).unwrap();
+        let squares = |s: &[&str]| {
+            s.iter().fold(BitBoard::ZERO, |bb, s| {
+                bb | BitBoard::from_square(square(s))
+            })
+        };
+
+        assert_eq!(
+            square("e4").neighbors(),
+            squares(&["d3", "e3", "f3", "d4", "f4", "d5", "e5", "f5"])
+        );
+        assert_eq!(
+            square("a4").neighbors(),
+            squares(&["a3", "b3", "b4", "a5", "b5"])
+        );
+        assert_eq!(square("h8").neighbors(), squares(&["g7", "h7", "g8"]));
+        assert_eq!(square("a1").neighbors(), squares(&["b1", "a2", "b2"]));
+
+        assert_eq!(square("e4").file_neighbors(), D_FILE | F_FILE);
+        assert_eq!(square("a1").file_neighbors(), B_FILE);
+        assert_eq!(square("h5").file_neighbors(), G_FILE);
+    }
+
+    #[test]
+    fn square_color() {
+        let square = |s| Square::from_str(s).unwrap();
+
+        assert!(square("a1").is_dark());
+        assert!(square("h1").is_light());
+        assert!(square("h8").is_dark());
+        assert!(square("d1").is_light());
+
+        // each side has a bishop of both colors
+        assert_ne!(square("c1").is_light(), square("f1").is_light());
+        assert_ne!(square("c8").is_light(), square("f8").is_light());
+    }
+
+    #[test]
+    fn square_distance() {
+        let square = |s| Square::from_str(s).unwrap();
+
+        assert_eq!(square("e4").distance(square("e4")), 0);
+        assert_eq!(square("e4").manhattan_distance(square("e4")), 0);
+
+        assert_eq!(square("e4").distance(square("f5")), 1);
+        assert_eq!(square("e4").manhattan_distance(square("f5")), 2);
+        assert_eq!(square("e4").distance(square("e5")), 1);
+        assert_eq!(square("e4").manhattan_distance(square("e5")), 1);
+
+        assert_eq!(square("a1").distance(square("h8")), 7);
+        assert_eq!(square("a1").manhattan_distance(square("h8")), 14);
+        assert_eq!(square("h1").distance(square("a1")), 7);
+        assert_eq!(square("h1").manhattan_distance(square("a1")), 7);
+    }
+}
+
+#[cfg(test)]
+mod board_tests {
+    use super::*;
+
+    #[test]
+    fn mirror() {
+        let start = Board::starting_position();
+        assert_eq!(start.mirror(), start);
+
+        let mut board = Board::default();
+        board.set_many([
+            (Square::at(0, 4).unwrap(), Piece::from_char('K').unwrap()),
+            (Square::at(3, 2).unwrap(), Piece::from_char('n').unwrap()),
+        ]);
+
+        let mirrored = board.mirror();
+        assert_eq!(
+            mirrored.get(Square::at(7, 4).unwrap()),
+            Piece::from_char('k')
+        );
+        assert_eq!(
+            mirrored.get(Square::at(4, 2).unwrap()),
+            Piece::from_char('N')
+        );
+        assert_eq!(mirrored.get_full_bb().0.count_ones(), 2);
+        assert_eq!(mirrored.mirror(), board);
+    }
+
+    #[test]
+    fn placement() {
+        let start = Board::starting_position();
+        assert_eq!(Board::from_placement(&start.placement()), start);
+
+        // queen odds
+        let mut placement = start.placement();
+        placement[Square::from_str("d1").unwrap().raw_index() as usize] = None;
+
+        let board = Board::from_placement(&placement);
+        for (index, piece) in placement.iter().enumerate() {
+            assert_eq!(board.get(Square::from_index_unchecked(index as u8)), *piece);
+        }
+        assert_eq!(board.get_full_bb().0.count_ones(), 31);
+        assert_eq!(board.placement(), placement);
+
+        assert!(Board::from_placement(&[None; 64]).is_empty());
+    }
+
+    #[test]
+    fn find_king() {
+        let start = Board::starting_position();
+        assert_eq!(start.find_king(Color::White), Square::at(0, 4));
+        assert_eq!(start.find_king(Color::Black), Square::at(7, 4));
+
+        let mut board = start.clone();
+        board.set(Square::at(7, 4).unwrap(), None);
+        assert_eq!(board.find_king(Color::Black), None);
+        assert_eq!(board.find_king(Color::White), Square::at(0, 4));
+
+        // not a single king
+        board.set(Square::at(3, 3).unwrap(), Piece::from_char('K'));
+        assert_eq!(board.find_king(Color::White), None);
+    }
+
+    #[test]
+    fn bulk_set() {
+        let mut board = Board::default();
+        assert!(board.is_empty());
+
+        let pieces = [
+            (Square::at(0, 0).unwrap(), Piece::from_char('R').unwrap()),
+            (Square::at(0, 4).unwrap(), Piece::from_char('K').unwrap()),
+            (Square::at(7, 4).unwrap(), Piece::from_char('k').unwrap()),
+        ];
+        board.set_many(pieces);
+
+        assert!(!board.is_empty());
+        for (square, piece) in pieces {
+            assert_eq!(board.get(square), Some(piece));
+        }
+
+        // replaces existing pieces
+        board.set_many([(Square::at(0, 0).unwrap(), Piece::from_char('q').unwrap())]);
+        assert_eq!(board.get(Square::at(0, 0).unwrap()), Piece::from_char('q'));
+        assert_eq!(board.get_full_bb().0.count_ones(), 3);
+
+        board.clear();
+        assert!(board.is_empty());
+        assert_eq!(board, Board::default());
+    }
 }
@@ -1,21 +1,44 @@
 //! Piece move information.
 
-use std::fmt::Display;
+use core::fmt::Display;
+use core::str::FromStr;
 
-use crate::{PieceKind, Square};
+use crate::{PieceKind, Position, Square};
 
 /// Minimal information required to represent a move in [LAN].
 ///
 /// [LAN]: https://www.chessprogramming.org/Algebraic_Chess_Notation#Long_Algebraic_Notation_.28LAN.29
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Move {
     pub from: Square,
     pub to: Square,
     pub promotion: Option<PieceKind>,
 }
 
+impl Move {
+    /// A move without promotion.
+    #[inline]
+    pub const fn new(from: Square, to: Square) -> Self {
+        Self {
+            from,
+            to,
+            promotion: None,
+        }
+    }
+
+    /// A pawn move promoting to `promotion`.
+    #[inline]
+    pub const fn promoting(from: Square, to: Square, promotion: PieceKind) -> Self {
+        Self {
+            from,
+            to,
+            promotion: Some(promotion),
+        }
+    }
+}
+
 impl Display for Move {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.from)?;
         write!(f, "{}", self.to)?;
 
@@ -27,14 +50,55 @@ impl Display for Move {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+impl FromStr for Move {
+    type Err = ();
+
+    /// Parse a move from [LAN], e.g. `e2e4` or `e7e8q`.
+    ///
+    /// [LAN]: https://www.chessprogramming.org/Algebraic_Chess_Notation#Long_Algebraic_Notation_.28LAN.29
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let from = s.get(0..2).ok_or(())?.parse()?;
+        let to = s.get(2..4).ok_or(())?.parse()?;
+
+        let mut promotion = s.get(4..).ok_or(())?.chars();
+        let promotion = match (promotion.next(), promotion.next()) {
+            (None, _) => None,
+            (Some(c), None) => Some(PieceKind::from_promotion_char(c).ok_or(())?),
+            _ => return Err(()),
+        };
+
+        Ok(Self {
+            from,
+            to,
+            promotion,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Capture {
     Regular(PieceKind),
-    EnPassant,
+    /// En passant capture of the pawn on this square.
+    EnPassant(Square),
+}
+
+impl Capture {
+    /// Kind of the captured piece.
+    ///
+    /// The captured piece always belongs to the opponent of the moving side.
+    #[inline]
+    pub const fn kind(&self) -> PieceKind {
+        match self {
+            Self::Regular(kind) => *kind,
+            Self::EnPassant(_) => PieceKind::Pawn,
+        }
+    }
 }
 
 /// Some additional info about a move to help with move ordering, application, etc.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MoveExt {
     pub piece_kind: PieceKind,
     pub from: Square,
@@ -63,26 +127,367 @@ impl MoveExt {
             promotion: self.promotion,
         }
     }
+
+    /// Check if this is a castling move, i.e, the king moves two files.
+    #[inline]
+    pub const fn is_castle(&self) -> bool {
+        matches!(self.piece_kind, PieceKind::King) && self.from.file().abs_diff(self.to.file()) == 2
+    }
+
+    /// Check if this is a king side castling move.
+    #[inline]
+    pub const fn is_kingside_castle(&self) -> bool {
+        self.is_castle() && self.to.file() > self.from.file()
+    }
+
+    /// Check if this is a queen side castling move.
+    #[inline]
+    pub const fn is_queenside_castle(&self) -> bool {
+        self.is_castle() && self.to.file() < self.from.file()
+    }
+
+    /// Check if this move captures a pawn en passant.
+    #[inline]
+    pub const fn is_en_passant(&self) -> bool {
+        matches!(self.capture, Some(Capture::EnPassant(_)))
+    }
+
+    /// Check if this move promotes a pawn.
+    #[inline]
+    pub const fn is_promotion(&self) -> bool {
+        self.promotion.is_some()
+    }
+
+    /// Check if this move captures a piece.
+    #[inline]
+    pub const fn is_capture(&self) -> bool {
+        self.capture.is_some()
+    }
+
+    /// Square of the captured piece, which is not the target square for en passant captures.
+    #[inline]
+    pub const fn captured_square(&self) -> Option<Square> {
+        match self.capture {
+            Some(Capture::Regular(_)) => Some(self.to),
+            Some(Capture::EnPassant(square)) => Some(square),
+            None => None,
+        }
+    }
+
+    /// Pack this move into 16 bits, see [`PackedMove`].
+    ///
+    /// Panics if the move promotes to a pawn or king.
+    pub const fn pack(&self) -> PackedMove {
+        let (promotion, flag) = match self.promotion {
+            Some(PieceKind::Knight) => (0, PackedMove::PROMOTION),
+            Some(PieceKind::Bishop) => (1, PackedMove::PROMOTION),
+            Some(PieceKind::Rook) => (2, PackedMove::PROMOTION),
+            Some(PieceKind::Queen) => (3, PackedMove::PROMOTION),
+            Some(PieceKind::Pawn | PieceKind::King) => panic!("invalid promotion piece"),
+            None if self.is_en_passant() => (0, PackedMove::EN_PASSANT),
+            None if self.is_castle() => (0, PackedMove::CASTLE),
+            None => (0, PackedMove::NORMAL),
+        };
+
+        PackedMove(
+            self.from.raw_index() as u16
+                | (self.to.raw_index() as u16) << 6
+                | promotion << 12
+                | flag << 14,
+        )
+    }
+}
+
+/// A move packed into 16 bits, for compact storage in hash tables and principal variations.
+///
+/// The layout is `from:6 | to:6 | promotion:2 | flags:2`, from the least significant bit. The
+/// moving and captured pieces are not stored, and are looked up again when unpacking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PackedMove(pub u16);
+
+impl PackedMove {
+    const NORMAL: u16 = 0;
+    const PROMOTION: u16 = 1;
+    const EN_PASSANT: u16 = 2;
+    const CASTLE: u16 = 3;
+
+    #[inline]
+    pub const fn from(&self) -> Square {
+        Square::from_index_unchecked((self.0 & 0x3f) as u8)
+    }
+
+    #[inline]
+    pub const fn to(&self) -> Square {
+        Square::from_index_unchecked((self.0 >> 6 & 0x3f) as u8)
+    }
+
+    #[inline]
+    const fn flag(&self) -> u16 {
+        self.0 >> 14
+    }
+
+    #[inline]
+    pub const fn promotion(&self) -> Option<PieceKind> {
+        if self.flag() != Self::PROMOTION {
+            return None;
+        }
+
+        Some(PieceKind::PROMOTABLE[(self.0 >> 12 & 0b11) as usize])
+    }
+
+    #[inline]
+    pub const fn to_move(&self) -> Move {
+        Move {
+            from: self.from(),
+            to: self.to(),
+            promotion: self.promotion(),
+        }
+    }
+
+    /// Restore the full move in the position it is played in.
+    ///
+    /// Returns `None` if there is no piece of the side to move on the source square, or the
+    /// target square holds one of its own pieces. This can happen with moves from another
+    /// position, e.g, after a hash collision.
+    pub fn unpack(&self, position: &Position) -> Option<MoveExt> {
+        let (from, to) = (self.from(), self.to());
+
+        let piece = position.board.get(from)?;
+        if piece.color != position.active_color
+            || position.board.get_color(to) == Some(position.active_color)
+        {
+            return None;
+        }
+
+        let capture = if self.flag() == Self::EN_PASSANT {
+            Some(Capture::EnPassant(Square::at(from.rank(), to.file())?))
+        } else {
+            position.board.get_piece_kind(to).map(Capture::Regular)
+        };
+
+        Some(MoveExt {
+            piece_kind: piece.kind,
+            from,
+            to,
+            promotion: self.promotion(),
+            capture,
+        })
+    }
 }
 
+/// Moves are displayed in [LAN], the same as [`Move`].
+///
+/// [LAN]: https://www.chessprogramming.org/Algebraic_Chess_Notation#Long_Algebraic_Notation_.28LAN.29
 impl Display for MoveExt {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.piece_kind != PieceKind::Pawn {
-            write!(f, "{}", self.piece_kind.as_char())?;
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.to_move().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn p_move(piece_kind: PieceKind, from: &str, to: &str) -> MoveExt {
+        MoveExt {
+            piece_kind,
+            from: Square::from_str(from).unwrap(),
+            to: Square::from_str(to).unwrap(),
+            promotion: None,
+            capture: None,
         }
+    }
 
-        write!(f, "{}", self.from)?;
+    #[test]
+    fn castle() {
+        let oo = p_move(PieceKind::King, "e1", "g1");
+        assert!(oo.is_castle());
+        assert!(oo.is_kingside_castle());
+        assert!(!oo.is_queenside_castle());
+
+        let ooo = p_move(PieceKind::King, "e8", "c8");
+        assert!(ooo.is_castle());
+        assert!(!ooo.is_kingside_castle());
+        assert!(ooo.is_queenside_castle());
 
-        if self.capture.is_some() {
-            write!(f, "x")?;
+        assert!(!p_move(PieceKind::King, "e1", "f1").is_castle());
+        assert!(!p_move(PieceKind::Rook, "e1", "g1").is_castle());
+        // two squares vertically
+        assert!(!p_move(PieceKind::King, "e1", "e3").is_castle());
+    }
+
+    #[test]
+    fn capture() {
+        let quiet = p_move(PieceKind::Knight, "g1", "f3");
+        assert!(!quiet.is_capture());
+        assert!(!quiet.is_en_passant());
+        assert_eq!(quiet.captured_square(), None);
+
+        let regular = MoveExt {
+            capture: Some(Capture::Regular(PieceKind::Bishop)),
+            ..p_move(PieceKind::Knight, "f3", "e5")
+        };
+        assert!(regular.is_capture());
+        assert!(!regular.is_en_passant());
+        assert_eq!(regular.captured_square(), Some(regular.to));
+        assert_eq!(regular.capture.unwrap().kind(), PieceKind::Bishop);
+
+        let ep = MoveExt {
+            capture: Some(Capture::EnPassant(Square::from_str("d5").unwrap())),
+            ..p_move(PieceKind::Pawn, "e5", "d6")
+        };
+        assert!(ep.is_capture());
+        assert!(ep.is_en_passant());
+        assert_eq!(ep.captured_square(), Some(Square::from_str("d5").unwrap()));
+        assert_eq!(ep.capture.unwrap().kind(), PieceKind::Pawn);
+    }
+
+    #[test]
+    fn promotion() {
+        let push = p_move(PieceKind::Pawn, "e7", "e8");
+        assert!(!push.is_promotion());
+
+        let promotion = MoveExt {
+            promotion: Some(PieceKind::Queen),
+            ..push
+        };
+        assert!(promotion.is_promotion());
+        assert!(!promotion.is_capture());
+    }
+
+    #[test]
+    fn const_moves() {
+        const fn sq(rank: u8, file: u8) -> Square {
+            Square::at(rank, file).unwrap()
         }
 
-        write!(f, "{}", self.to)?;
+        const LINE: [Move; 3] = [
+            Move::new(sq(1, 4), sq(3, 4)),
+            Move::new(sq(6, 4), sq(4, 4)),
+            Move::promoting(sq(6, 0), sq(7, 0), PieceKind::Queen),
+        ];
 
-        if let Some(promotion) = self.promotion {
-            write!(f, "{}", promotion.as_char().to_ascii_lowercase())?;
+        assert_eq!(LINE[0], Move::from_str("e2e4").unwrap());
+        assert_eq!(LINE[1], Move::from_str("e7e5").unwrap());
+        assert_eq!(LINE[2], Move::from_str("a7a8q").unwrap());
+    }
+
+    #[test]
+    fn display_lan() {
+        let moves = [
+            p_move(PieceKind::Knight, "g1", "f3"),
+            MoveExt {
+                capture: Some(Capture::Regular(PieceKind::Pawn)),
+                ..p_move(PieceKind::Knight, "e2", "e4")
+            },
+            MoveExt {
+                capture: Some(Capture::Regular(PieceKind::Rook)),
+                promotion: Some(PieceKind::Queen),
+                ..p_move(PieceKind::Pawn, "e7", "d8")
+            },
+        ];
+
+        assert_eq!(moves[1].to_string(), "e2e4");
+        assert_eq!(moves[2].to_string(), "e7d8q");
+
+        for p_move in moves {
+            let lan = p_move.to_string();
+            assert_eq!(Move::from_str(&lan), Ok(p_move.to_move()));
+            assert_eq!(lan, p_move.to_move().to_string());
         }
+    }
 
-        Ok(())
+    #[test]
+    fn packed() {
+        use crate::{Color, Piece};
+
+        let sq = |s| Square::from_str(s).unwrap();
+        let white = |kind| Piece {
+            color: Color::White,
+            kind,
+        };
+        let black = |kind| Piece {
+            color: Color::Black,
+            kind,
+        };
+
+        let position = Position::builder()
+            .piece(sq("e1"), white(PieceKind::King))
+            .piece(sq("h1"), white(PieceKind::Rook))
+            .piece(sq("b1"), white(PieceKind::Knight))
+            .piece(sq("e5"), white(PieceKind::Pawn))
+            .piece(sq("g7"), white(PieceKind::Pawn))
+            .piece(sq("e8"), black(PieceKind::King))
+            .piece(sq("h8"), black(PieceKind::Rook))
+            .piece(sq("d5"), black(PieceKind::Pawn))
+            .ep(sq("d6"))
+            .build();
+
+        let moves = [
+            p_move(PieceKind::Knight, "b1", "c3"),
+            p_move(PieceKind::King, "e1", "f1"),
+            p_move(PieceKind::King, "e1", "g1"),
+            MoveExt {
+                capture: Some(Capture::EnPassant(sq("d5"))),
+                ..p_move(PieceKind::Pawn, "e5", "d6")
+            },
+            MoveExt {
+                capture: Some(Capture::Regular(PieceKind::Rook)),
+                promotion: Some(PieceKind::Knight),
+                ..p_move(PieceKind::Pawn, "g7", "h8")
+            },
+            MoveExt {
+                promotion: Some(PieceKind::Queen),
+                ..p_move(PieceKind::Pawn, "g7", "g8")
+            },
+        ];
+
+        for p_move in moves {
+            let packed = p_move.pack();
+            assert_eq!(packed.to_move(), p_move.to_move());
+            assert_eq!(packed.unpack(&position), Some(p_move));
+        }
+
+        // no piece of the side to move on the source square
+        assert_eq!(
+            p_move(PieceKind::Rook, "h8", "h7").pack().unpack(&position),
+            None
+        );
+        assert_eq!(
+            p_move(PieceKind::Pawn, "a2", "a3").pack().unpack(&position),
+            None
+        );
+
+        // capture of a piece of the side to move
+        assert_eq!(
+            p_move(PieceKind::Rook, "h1", "e1").pack().unpack(&position),
+            None
+        );
+    }
+
+    #[test]
+    fn lan() {
+        for lan in ["e2e4", "e7e8q", "a2a1n"] {
+            assert_eq!(Move::from_str(lan).unwrap().to_string(), lan);
+        }
+
+        assert_eq!(
+            Move::from_str("E2E4").unwrap(),
+            Move::from_str("e2e4").unwrap()
+        );
+
+        for lan in [
+            "",
+            "e2",
+            "e2e",
+            "e2e9",
+            "e7e8k",
+            "e7e8p",
+            "e7e8qq",
+            "e7e8queen",
+        ] {
+            assert!(Move::from_str(lan).is_err());
+        }
     }
 }
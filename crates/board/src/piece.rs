@@ -1,6 +1,8 @@
 //! Piece specific definitions.
 
-use std::ops::Not;
+use core::fmt::Display;
+use core::ops::Not;
+use core::str::FromStr;
 
 use strum::{EnumCount, EnumIter, FromRepr};
 
@@ -36,6 +38,30 @@ impl Not for Color {
     }
 }
 
+impl Display for Color {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            White => write!(f, "white"),
+            Black => write!(f, "black"),
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = ();
+
+    /// Parse a color from its name or initial, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("white") || s.eq_ignore_ascii_case("w") {
+            Ok(White)
+        } else if s.eq_ignore_ascii_case("black") || s.eq_ignore_ascii_case("b") {
+            Ok(Black)
+        } else {
+            Err(())
+        }
+    }
+}
+
 /// All possible piece types.
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumCount, EnumIter, FromRepr)]
@@ -51,6 +77,26 @@ pub enum PieceKind {
 impl PieceKind {
     pub const PROMOTABLE: [Self; 4] = [Knight, Bishop, Rook, Queen];
 
+    /// Check if a pawn can promote to this piece kind.
+    #[inline]
+    pub const fn is_promotable(&self) -> bool {
+        matches!(self, Knight | Bishop | Rook | Queen)
+    }
+
+    /// Parse a promotion piece from its standard notation, ignoring case.
+    ///
+    /// Returns `None` for pawns and kings.
+    #[inline]
+    pub const fn from_promotion_char(c: char) -> Option<Self> {
+        match c.to_ascii_uppercase() {
+            'N' => Some(Knight),
+            'B' => Some(Bishop),
+            'R' => Some(Rook),
+            'Q' => Some(Queen),
+            _ => None,
+        }
+    }
+
     /// Standard notation for this piece kind.
     #[inline]
     #[rustfmt::skip]
@@ -65,6 +111,20 @@ impl PieceKind {
         }
     }
 
+    /// Lowercase name of this piece kind.
+    #[inline]
+    #[rustfmt::skip]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Pawn   => "pawn",
+            Knight => "knight",
+            Bishop => "bishop",
+            Rook   => "rook",
+            Queen  => "queen",
+            King   => "king",
+        }
+    }
+
     /// Piece valuation on some arbitrary scale.
     #[inline]
     #[rustfmt::skip]
@@ -87,6 +147,29 @@ impl PieceKind {
     }
 }
 
+impl Display for PieceKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for PieceKind {
+    type Err = ();
+
+    /// Parse a piece kind from its name or standard notation, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use strum::IntoEnumIterator;
+
+        Self::iter()
+            .find(|kind| {
+                let mut c = [0; 4];
+                s.eq_ignore_ascii_case(kind.as_char().encode_utf8(&mut c))
+                    || s.eq_ignore_ascii_case(kind.name())
+            })
+            .ok_or(())
+    }
+}
+
 /// Piece belonging to a side.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Piece {
@@ -141,3 +224,64 @@ impl Piece {
         Some(Self { color, kind })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    #[test]
+    fn color_str() {
+        assert_eq!(White.to_string(), "white");
+        assert_eq!(Black.to_string(), "black");
+
+        for color in Color::iter() {
+            let name = color.to_string();
+            assert_eq!(Color::from_str(&name), Ok(color));
+            assert_eq!(Color::from_str(&name.to_uppercase()), Ok(color));
+        }
+
+        assert_eq!(Color::from_str("W"), Ok(White));
+        assert_eq!(Color::from_str("b"), Ok(Black));
+        assert!(Color::from_str("red").is_err());
+        assert!(Color::from_str("").is_err());
+    }
+
+    #[test]
+    fn piece_kind_str() {
+        assert_eq!(Knight.to_string(), "knight");
+
+        for kind in PieceKind::iter() {
+            let name = kind.to_string();
+            assert_eq!(PieceKind::from_str(&name), Ok(kind));
+            assert_eq!(PieceKind::from_str(&name.to_uppercase()), Ok(kind));
+
+            let c = kind.as_char().to_string();
+            assert_eq!(PieceKind::from_str(&c), Ok(kind));
+            assert_eq!(PieceKind::from_str(&c.to_lowercase()), Ok(kind));
+        }
+
+        assert!(PieceKind::from_str("x").is_err());
+        assert!(PieceKind::from_str("kings").is_err());
+    }
+
+    #[test]
+    fn promotion() {
+        for kind in PieceKind::iter() {
+            assert_eq!(kind.is_promotable(), PieceKind::PROMOTABLE.contains(&kind));
+
+            let c = kind.as_char();
+            let expected = kind.is_promotable().then_some(kind);
+            assert_eq!(PieceKind::from_promotion_char(c), expected);
+            assert_eq!(
+                PieceKind::from_promotion_char(c.to_ascii_lowercase()),
+                expected
+            );
+        }
+
+        assert_eq!(PieceKind::from_promotion_char('K'), None);
+        assert_eq!(PieceKind::from_promotion_char('p'), None);
+        assert_eq!(PieceKind::from_promotion_char('x'), None);
+    }
+}
@@ -1,6 +1,8 @@
 //! The full game position.
 
-use crate::{bitboard, BitBoard, Board, Capture, Color, MoveExt, PieceKind, Square};
+use core::hash::{Hash, Hasher};
+
+use crate::{bitboard, BitBoard, Board, Capture, Color, MoveExt, Piece, PieceKind, Square};
 
 bitflags::bitflags! {
     /// Player castling availability.
@@ -17,21 +19,58 @@ bitflags::bitflags! {
 }
 
 impl CastlingRights {
+    /// The kingside castling right of `color`.
     #[inline]
-    pub fn unset_oo(self, color: Color) -> Self {
+    pub const fn kingside(color: Color) -> Self {
         match color {
-            Color::White => self & !Self::WHITE_OO,
-            Color::Black => self & !Self::BLACK_OO,
+            Color::White => Self::WHITE_OO,
+            Color::Black => Self::BLACK_OO,
         }
     }
 
+    /// The queenside castling right of `color`.
     #[inline]
-    pub fn unset_ooo(self, color: Color) -> Self {
+    pub const fn queenside(color: Color) -> Self {
         match color {
-            Color::White => self & !Self::WHITE_OO,
-            Color::Black => self & !Self::BLACK_OO,
+            Color::White => Self::WHITE_OOO,
+            Color::Black => Self::BLACK_OOO,
         }
     }
+
+    /// Check if `color` may castle kingside.
+    #[inline]
+    pub const fn has_kingside(self, color: Color) -> bool {
+        self.contains(Self::kingside(color))
+    }
+
+    /// Check if `color` may castle queenside.
+    #[inline]
+    pub const fn has_queenside(self, color: Color) -> bool {
+        self.contains(Self::queenside(color))
+    }
+
+    #[inline]
+    pub fn unset_oo(self, color: Color) -> Self {
+        self & !Self::kingside(color)
+    }
+
+    #[inline]
+    pub fn unset_ooo(self, color: Color) -> Self {
+        self & !Self::queenside(color)
+    }
+
+    /// An iterator over the individual rights that are set.
+    #[inline]
+    pub fn iter(self) -> impl Iterator<Item = Self> {
+        [
+            Self::WHITE_OO,
+            Self::WHITE_OOO,
+            Self::BLACK_OO,
+            Self::BLACK_OOO,
+        ]
+        .into_iter()
+        .filter(move |right| self.contains(*right))
+    }
 }
 
 /// Full chessboard state.
@@ -54,7 +93,7 @@ pub struct Position {
     ///
     /// A full-move consists of two half-moves, one by white and one by black. This counts the total
     /// number of moves since the game began. It starts at 1 and increments after black's move.
-    pub fullmove_counter: u8,
+    pub fullmove_counter: u16,
 }
 
 impl Position {
@@ -69,13 +108,158 @@ impl Position {
         }
     }
 
+    /// Start building a position from an empty board.
+    #[inline]
+    pub fn builder() -> PositionBuilder {
+        PositionBuilder::new()
+    }
+
+    /// Generate a random, possibly illegal position.
+    ///
+    /// Both kings are always present and not next to each other, and pawns are never placed
+    /// on the first or last ranks. There are no castling rights or en passant target. The side
+    /// not to move may be in check, which can't be checked without a move generator.
+    ///
+    /// This is only the placement step of `sealion_engine::random::random_position`, which
+    /// rejects the illegal positions and should be used instead.
+    #[cfg(feature = "rand")]
+    #[doc(hidden)]
+    pub fn random<R: rand::Rng + ?Sized>(rng: &mut R) -> Self {
+        let mut board = Board::default();
+
+        let white_king = Square::from_index_unchecked(rng.gen_range(0..64));
+        let black_king = loop {
+            let square = Square::from_index_unchecked(rng.gen_range(0..64));
+
+            if square.distance(white_king) > 1 {
+                break square;
+            }
+        };
+
+        board.set(
+            white_king,
+            Some(Piece {
+                color: Color::White,
+                kind: PieceKind::King,
+            }),
+        );
+        board.set(
+            black_king,
+            Some(Piece {
+                color: Color::Black,
+                kind: PieceKind::King,
+            }),
+        );
+
+        for _ in 0..rng.gen_range(0..=30) {
+            let square = Square::from_index_unchecked(rng.gen_range(0..64));
+            let kind = PieceKind::from_repr(rng.gen_range(0..5)).unwrap();
+            let color = Color::from_repr(rng.gen_range(0..2)).unwrap();
+
+            let pawn_rank = square.rank() == 0 || square.rank() == 7;
+            if board.get_full_bb().get(square) || (kind == PieceKind::Pawn && pawn_rank) {
+                continue;
+            }
+
+            board.set(square, Some(Piece { color, kind }));
+        }
+
+        Position {
+            board,
+            active_color: Color::from_repr(rng.gen_range(0..2)).unwrap(),
+            castling: CastlingRights::empty(),
+            ep_target: None,
+            halfmove_clock: 0,
+            fullmove_counter: 1,
+        }
+    }
+
+    /// Parse a position from the given fen string.
+    #[cfg(feature = "fen")]
+    #[inline]
+    pub fn from_fen(s: &str) -> Result<Self, crate::fen::FenError> {
+        crate::fen::from_str(s)
+    }
+
+    /// Serialize this position into a fen string.
+    #[cfg(feature = "fen")]
+    #[inline]
+    pub fn to_fen(&self) -> String {
+        crate::fen::to_string(self)
+    }
+
+    /// The en passant target square, only if a pawn of the side to move can capture on it.
+    ///
+    /// A double pawn push always sets [`Position::ep_target`], even when no enemy pawn is
+    /// adjacent. Positions that differ only by such an uncapturable target are otherwise
+    /// identical, so this should be preferred when comparing or hashing positions.
+    pub fn normalized_ep(&self) -> Option<Square> {
+        let ep_target = self.ep_target?;
+        let ep_bb = BitBoard::from_square(ep_target);
+
+        let ep_rank = match self.active_color {
+            Color::White => 5,
+            Color::Black => 2,
+        };
+        if ep_target.rank() != ep_rank {
+            return None;
+        }
+
+        let capturers = match self.active_color {
+            Color::White => {
+                ((ep_bb >> 9) & !bitboard::constants::H_FILE)
+                    | ((ep_bb >> 7) & !bitboard::constants::A_FILE)
+            }
+            Color::Black => {
+                ((ep_bb << 7) & !bitboard::constants::H_FILE)
+                    | ((ep_bb << 9) & !bitboard::constants::A_FILE)
+            }
+        };
+
+        let pawns = self.board.get_piece_bb(Piece {
+            color: self.active_color,
+            kind: PieceKind::Pawn,
+        });
+
+        (capturers & pawns != 0).then_some(ep_target)
+    }
+
+    /// Check if both positions are the same for the purposes of repetition.
+    ///
+    /// Unlike `==`, this ignores the move clocks, and only considers en passant targets that
+    /// can actually be captured (see [`Position::normalized_ep`]).
+    pub fn same_position(&self, other: &Position) -> bool {
+        self.board == other.board
+            && self.active_color == other.active_color
+            && self.castling == other.castling
+            && self.normalized_ep() == other.normalized_ep()
+    }
+
+    /// Check if the game can be claimed as a draw by the 50-move rule.
+    ///
+    /// This doesn't consider checkmate on the 100th ply, which takes precedence.
+    #[inline]
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.halfmove_clock >= 100
+    }
+
     /// Reset castle flags if a rook on `square_bb` changes.
+    ///
+    /// Only rooks on their starting corners affect castling, which may belong to either side
+    /// when a rook is captured.
     #[inline]
     fn reset_rook_castling(&mut self, square_bb: BitBoard) {
-        if square_bb & bitboard::constants::A_FILE != 0 {
-            self.castling = self.castling.unset_ooo(self.active_color)
-        } else if square_bb & bitboard::constants::H_FILE != 0 {
-            self.castling = self.castling.unset_oo(self.active_color)
+        const CORNERS: [(u64, CastlingRights); 4] = [
+            (1 << 0, CastlingRights::WHITE_OOO),
+            (1 << 7, CastlingRights::WHITE_OO),
+            (1 << 56, CastlingRights::BLACK_OOO),
+            (1 << 63, CastlingRights::BLACK_OO),
+        ];
+
+        for (corner, rights) in CORNERS {
+            if square_bb & BitBoard(corner) != 0 {
+                self.castling &= !rights;
+            }
         }
     }
 
@@ -99,10 +283,8 @@ impl Position {
             self.castling = self.castling.unset_ooo(self.active_color);
 
             // do castles
-            if p_move.from.raw_index().abs_diff(p_move.to.raw_index()) == 2 {
-                // queen side
-                let (rook_from_sq, rook_to_sq) = if p_move.to.raw_index() < p_move.from.raw_index()
-                {
+            if p_move.is_castle() {
+                let (rook_from_sq, rook_to_sq) = if p_move.is_queenside_castle() {
                     let rfs = from_sq >> 4;
                     let rts = from_sq >> 1;
                     (rfs, rts)
@@ -159,30 +341,384 @@ impl Position {
             Some(Capture::Regular(cap)) => {
                 *self.board.get_color_bb_mut(self.active_color.opposite()) &= !to_sq;
                 *self.board.get_piece_kind_bb_mut(cap) &= !to_sq;
-                *self.board.get_piece_kind_bb_mut(p_move.piece_kind) |= to_sq; // in case they're the same type
+                // in case they're the same type, or the pawn promoted to the captured type
+                let moved_kind = p_move.promotion.unwrap_or(p_move.piece_kind);
+                *self.board.get_piece_kind_bb_mut(moved_kind) |= to_sq;
 
                 if cap == PieceKind::Rook {
                     self.reset_rook_castling(to_sq);
                 }
             }
-            Some(Capture::EnPassant) => {
-                let captured_sq = match self.active_color {
-                    Color::White => to_sq >> 8,
-                    Color::Black => to_sq << 8,
-                };
+            Some(Capture::EnPassant(captured_sq)) => {
+                let captured_sq = BitBoard::from_square(captured_sq);
                 *self.board.get_color_bb_mut(self.active_color.opposite()) &= !captured_sq;
                 *self.board.get_piece_kind_bb_mut(PieceKind::Pawn) &= !captured_sq;
             }
             _ => {}
         }
 
-        // increment counters
-        if p_move.capture.is_none() || p_move.piece_kind != PieceKind::Pawn {
-            self.halfmove_clock += 1;
+        // update counters
+        if p_move.capture.is_some() || p_move.piece_kind == PieceKind::Pawn {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock = self.halfmove_clock.saturating_add(1);
         }
         if self.active_color == Color::Black {
-            self.fullmove_counter += 1;
+            self.fullmove_counter = self.fullmove_counter.saturating_add(1);
         }
         self.active_color = self.active_color.opposite();
     }
 }
+
+/// Assert that two positions are the same, ignoring the move clocks.
+///
+/// Compares positions with [`Position::same_position`], so tests don't fail when only the
+/// counters differ.
+#[macro_export]
+macro_rules! assert_position_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                if !$crate::Position::same_position(left, right) {
+                    panic!(
+                        "assertion failed: `left.same_position(right)`\n  left: {:?}\n right: {:?}",
+                        left, right
+                    );
+                }
+            }
+        }
+    };
+}
+
+/// A [`Position`] compared and hashed by [`Position::same_position`].
+///
+/// The derived `Hash` and `Eq` on [`Position`] consider every field, including the move clocks,
+/// so the same position reached at different points in a game compares unequal. Use this as a
+/// map key instead when looking for repetitions or transpositions.
+#[derive(Debug, Clone)]
+pub struct PositionKey(pub Position);
+
+impl From<Position> for PositionKey {
+    #[inline]
+    fn from(position: Position) -> Self {
+        Self(position)
+    }
+}
+
+impl PartialEq for PositionKey {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.0.same_position(&other.0)
+    }
+}
+
+impl Eq for PositionKey {}
+
+impl Hash for PositionKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.board.hash(state);
+        self.0.active_color.hash(state);
+        self.0.castling.hash(state);
+        self.0.normalized_ep().hash(state);
+    }
+}
+
+/// Fluent builder for arbitrary positions.
+///
+/// Starts from an empty board with white to move, no castling rights or en passant target,
+/// and fresh move counters.
+#[must_use]
+#[derive(Debug, Clone)]
+pub struct PositionBuilder {
+    position: Position,
+}
+
+impl Default for PositionBuilder {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PositionBuilder {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            position: Position {
+                board: Board::default(),
+                active_color: Color::White,
+                castling: CastlingRights::empty(),
+                ep_target: None,
+                halfmove_clock: 0,
+                fullmove_counter: 1,
+            },
+        }
+    }
+
+    /// Place a piece on a square, replacing any existing piece.
+    #[inline]
+    pub fn piece(mut self, square: Square, piece: Piece) -> Self {
+        self.position.board.set_many([(square, piece)]);
+        self
+    }
+
+    /// Set the side to move.
+    #[inline]
+    pub fn active(mut self, color: Color) -> Self {
+        self.position.active_color = color;
+        self
+    }
+
+    /// Set the castling rights.
+    #[inline]
+    pub fn castling(mut self, rights: CastlingRights) -> Self {
+        self.position.castling = rights;
+        self
+    }
+
+    /// Set the en passant target square.
+    #[inline]
+    pub fn ep(mut self, square: Square) -> Self {
+        self.position.ep_target = Some(square);
+        self
+    }
+
+    /// Set the half-move clock.
+    #[inline]
+    pub fn halfmove_clock(mut self, clock: u8) -> Self {
+        self.position.halfmove_clock = clock;
+        self
+    }
+
+    /// Set the full-move counter.
+    #[inline]
+    pub fn fullmove_counter(mut self, counter: u16) -> Self {
+        self.position.fullmove_counter = counter;
+        self
+    }
+
+    #[inline]
+    pub fn build(self) -> Position {
+        self.position
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Move;
+
+    #[test]
+    fn normalized_ep() {
+        // e2e4 without any black pawns nearby
+        let mut position = Position::starting();
+        position.apply_move_unchecked(MoveExt::from_move(
+            Move {
+                from: Square::at(1, 4).unwrap(),
+                to: Square::at(3, 4).unwrap(),
+                promotion: None,
+            },
+            PieceKind::Pawn,
+        ));
+
+        assert_eq!(position.ep_target, Square::at(2, 4));
+        assert_eq!(position.normalized_ep(), None);
+
+        // a black pawn on d4 can capture
+        position.board.set(
+            Square::at(3, 3).unwrap(),
+            Some(Piece {
+                color: Color::Black,
+                kind: PieceKind::Pawn,
+            }),
+        );
+
+        assert_eq!(position.normalized_ep(), Square::at(2, 4));
+
+        // a white pawn on d4 can't
+        position.board.set(Square::at(3, 3).unwrap(), None);
+        position.board.set(
+            Square::at(3, 3).unwrap(),
+            Some(Piece {
+                color: Color::White,
+                kind: PieceKind::Pawn,
+            }),
+        );
+
+        assert_eq!(position.normalized_ep(), None);
+    }
+
+    #[test]
+    fn counter_overflow() {
+        let mut position = Position::starting();
+        position.active_color = Color::Black;
+        position.halfmove_clock = u8::MAX;
+        position.fullmove_counter = u16::MAX;
+
+        // Nb8c6
+        position.apply_move_unchecked(MoveExt::from_move(
+            Move {
+                from: Square::at(7, 1).unwrap(),
+                to: Square::at(5, 2).unwrap(),
+                promotion: None,
+            },
+            PieceKind::Knight,
+        ));
+
+        assert_eq!(position.halfmove_clock, u8::MAX);
+        assert_eq!(position.fullmove_counter, u16::MAX);
+    }
+
+    #[cfg(feature = "fen")]
+    #[test]
+    fn fen_round_trip() {
+        const FENS: [&str; 4] = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2",
+        ];
+
+        assert_eq!(Position::from_fen(FENS[0]).unwrap(), Position::starting());
+        assert_eq!(Position::starting().to_fen(), FENS[0]);
+
+        for fen in FENS {
+            assert_eq!(Position::from_fen(fen).unwrap().to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn builder() {
+        let board = Board::starting_position();
+        let mut builder = Position::builder()
+            .active(Color::White)
+            .castling(CastlingRights::all());
+
+        for square in board.get_full_bb().set_iter() {
+            builder = builder.piece(square, board.get(square).unwrap());
+        }
+
+        assert_eq!(builder.build(), Position::starting());
+
+        let position = Position::builder()
+            .piece(
+                Square::at(0, 4).unwrap(),
+                Piece {
+                    color: Color::White,
+                    kind: PieceKind::King,
+                },
+            )
+            .active(Color::Black)
+            .ep(Square::at(2, 3).unwrap())
+            .fullmove_counter(20)
+            .build();
+
+        assert_eq!(position.active_color, Color::Black);
+        assert_eq!(position.castling, CastlingRights::empty());
+        assert_eq!(position.ep_target, Square::at(2, 3));
+        assert_eq!(position.halfmove_clock, 0);
+        assert_eq!(position.fullmove_counter, 20);
+        assert_eq!(position.board.get_full_bb().0.count_ones(), 1);
+    }
+
+    #[test]
+    fn position_key() {
+        use std::collections::hash_map::DefaultHasher;
+
+        fn hash(key: &PositionKey) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            key.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let position = Position::starting();
+        let mut other = position.clone();
+        other.halfmove_clock = 4;
+        other.fullmove_counter = 3;
+
+        let key = PositionKey::from(position);
+        let other = PositionKey::from(other);
+        assert_eq!(key, other);
+        assert_eq!(hash(&key), hash(&other));
+
+        let mut black = key.clone();
+        black.0.active_color = Color::Black;
+        assert_ne!(key, black);
+    }
+
+    #[test]
+    fn castling_rights() {
+        for color in [Color::White, Color::Black] {
+            let rights = CastlingRights::all();
+            assert!(rights.has_kingside(color));
+            assert!(rights.has_queenside(color));
+
+            let rights = CastlingRights::all().unset_oo(color);
+            assert!(!rights.has_kingside(color));
+            assert!(rights.has_queenside(color));
+            assert!(rights.has_kingside(!color));
+            assert!(rights.has_queenside(!color));
+
+            let rights = CastlingRights::all().unset_ooo(color);
+            assert!(rights.has_kingside(color));
+            assert!(!rights.has_queenside(color));
+            assert!(rights.has_kingside(!color));
+            assert!(rights.has_queenside(!color));
+        }
+
+        assert_eq!(CastlingRights::all().iter().count(), 4);
+        assert_eq!(CastlingRights::empty().iter().count(), 0);
+        assert_eq!(
+            (CastlingRights::WHITE_OOO | CastlingRights::BLACK_OO)
+                .iter()
+                .collect::<Vec<_>>(),
+            [CastlingRights::WHITE_OOO, CastlingRights::BLACK_OO]
+        );
+    }
+
+    #[test]
+    fn assert_position_eq() {
+        let position = Position::starting();
+        let mut other = position.clone();
+        other.halfmove_clock = 8;
+        other.fullmove_counter = 5;
+
+        assert_position_eq!(position, other);
+        assert_position_eq!(&position, &position.clone());
+    }
+
+    #[test]
+    #[should_panic(expected = "same_position")]
+    fn assert_position_ne() {
+        let position = Position::starting();
+        let mut other = position.clone();
+        other.active_color = Color::Black;
+
+        assert_position_eq!(position, other);
+    }
+
+    #[test]
+    fn same_position() {
+        let position = Position::starting();
+        let mut other = position.clone();
+        other.halfmove_clock = 8;
+        other.fullmove_counter = 5;
+
+        assert!(position.same_position(&other));
+        assert_ne!(position, other);
+
+        other.active_color = Color::Black;
+        assert!(!position.same_position(&other));
+
+        // uncapturable ep targets are ignored
+        let mut position = position.clone();
+        position.active_color = Color::Black;
+        let mut other = position.clone();
+        other.ep_target = Square::at(2, 4);
+        assert!(position.same_position(&other));
+
+        let mut other = position.clone();
+        other.castling = other.castling.unset_oo(Color::White);
+        assert!(!position.same_position(&other));
+    }
+}
@@ -0,0 +1,350 @@
+//! [`serde`] support, behind the `serde` feature.
+//!
+//! Squares and moves are represented in algebraic notation, and positions as FEN strings (with
+//! the `fen` feature).
+
+use core::fmt::{self, Display, Write};
+use core::marker::PhantomData;
+use core::str::FromStr;
+
+use ::serde::de::{self, Deserializer, Unexpected, Visitor};
+use ::serde::ser::Serializer;
+use ::serde::{Deserialize, Serialize};
+
+use crate::{CastlingRights, Color, Move, Piece, PieceKind, Square};
+
+/// Small stack buffer to format short values into, without allocating.
+struct StrBuf {
+    buf: [u8; 8],
+    len: usize,
+}
+
+impl StrBuf {
+    const fn new() -> Self {
+        Self {
+            buf: [0; 8],
+            len: 0,
+        }
+    }
+
+    fn format<T: Display>(value: &T) -> Result<Self, fmt::Error> {
+        let mut buf = Self::new();
+        write!(buf, "{value}")?;
+        Ok(buf)
+    }
+
+    /// Push a character, ignoring it if the buffer is full.
+    fn push(&mut self, c: char) {
+        let _ = self.write_char(c);
+    }
+
+    fn as_str(&self) -> &str {
+        // only ever written to from `&str`s
+        core::str::from_utf8(&self.buf[..self.len]).unwrap()
+    }
+}
+
+impl Write for StrBuf {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        self.buf
+            .get_mut(self.len..end)
+            .ok_or(fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// Serialize a value through its [`Display`] implementation.
+fn serialize_display<T: Display, S: Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let buf = StrBuf::format(value).map_err(::serde::ser::Error::custom)?;
+    serializer.serialize_str(buf.as_str())
+}
+
+/// Deserialize a value through its [`FromStr`] implementation.
+struct FromStrVisitor<T> {
+    expecting: &'static str,
+    _marker: PhantomData<T>,
+}
+
+impl<T> FromStrVisitor<T> {
+    const fn new(expecting: &'static str) -> Self {
+        Self {
+            expecting,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, T: FromStr> Visitor<'de> for FromStrVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.expecting)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        T::from_str(v).map_err(|_| E::invalid_value(Unexpected::Str(v), &self))
+    }
+}
+
+impl Serialize for Square {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_display(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Square {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(FromStrVisitor::new("a square in algebraic notation"))
+    }
+}
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_display(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(FromStrVisitor::new("a color"))
+    }
+}
+
+impl Serialize for PieceKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for PieceKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(FromStrVisitor::new("a piece kind"))
+    }
+}
+
+impl Serialize for Piece {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_char(self.as_char())
+    }
+}
+
+impl<'de> Deserialize<'de> for Piece {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PieceVisitor;
+
+        impl<'de> Visitor<'de> for PieceVisitor {
+            type Value = Piece;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a piece in standard notation")
+            }
+
+            fn visit_char<E: de::Error>(self, v: char) -> Result<Self::Value, E> {
+                Piece::from_char(v).ok_or_else(|| E::invalid_value(Unexpected::Char(v), &self))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                let mut chars = v.chars();
+
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => self.visit_char(c),
+                    _ => Err(E::invalid_value(Unexpected::Str(v), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_char(PieceVisitor)
+    }
+}
+
+impl Serialize for Move {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_display(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Move {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(FromStrVisitor::new("a move in long algebraic notation"))
+    }
+}
+
+const CASTLING_CHARS: [(CastlingRights, char); 4] = [
+    (CastlingRights::WHITE_OO, 'K'),
+    (CastlingRights::WHITE_OOO, 'Q'),
+    (CastlingRights::BLACK_OO, 'k'),
+    (CastlingRights::BLACK_OOO, 'q'),
+];
+
+/// Castling rights are represented as in FEN, e.g. `KQkq` or `-`.
+impl Serialize for CastlingRights {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut buf = StrBuf::new();
+
+        if self.is_empty() {
+            buf.push('-');
+        }
+        for (flag, c) in CASTLING_CHARS {
+            if self.contains(flag) {
+                buf.push(c);
+            }
+        }
+
+        serializer.serialize_str(buf.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for CastlingRights {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct CastlingVisitor;
+
+        impl<'de> Visitor<'de> for CastlingVisitor {
+            type Value = CastlingRights;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("castling rights in FEN notation")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                if v == "-" {
+                    return Ok(CastlingRights::empty());
+                }
+
+                let mut rights = CastlingRights::empty();
+
+                for c in v.chars() {
+                    let (flag, _) = CASTLING_CHARS
+                        .into_iter()
+                        .find(|(_, flag_c)| *flag_c == c)
+                        .ok_or_else(|| E::invalid_value(Unexpected::Str(v), &self))?;
+                    rights |= flag;
+                }
+
+                if rights.is_empty() {
+                    return Err(E::invalid_value(Unexpected::Str(v), &self));
+                }
+
+                Ok(rights)
+            }
+        }
+
+        deserializer.deserialize_str(CastlingVisitor)
+    }
+}
+
+/// Positions are represented as FEN strings.
+#[cfg(feature = "fen")]
+impl Serialize for crate::Position {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_fen())
+    }
+}
+
+#[cfg(feature = "fen")]
+impl<'de> Deserialize<'de> for crate::Position {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PositionVisitor;
+
+        impl<'de> Visitor<'de> for PositionVisitor {
+            type Value = crate::Position;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a position in FEN notation")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                crate::Position::from_fen(v)
+                    .map_err(|_| E::invalid_value(Unexpected::Str(v), &self))
+            }
+        }
+
+        deserializer.deserialize_str(PositionVisitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::fmt::Debug;
+
+    use ::serde::de::DeserializeOwned;
+
+    use super::*;
+    use crate::{Capture, MoveExt};
+
+    fn round_trip<T>(value: T, json: &str)
+    where
+        T: Serialize + DeserializeOwned + PartialEq + Debug,
+    {
+        assert_eq!(serde_json::to_string(&value).unwrap(), json);
+        assert_eq!(serde_json::from_str::<T>(json).unwrap(), value);
+    }
+
+    #[test]
+    fn square() {
+        round_trip(Square::from_str("e4").unwrap(), r#""e4""#);
+        assert!(serde_json::from_str::<Square>(r#""e9""#).is_err());
+    }
+
+    #[test]
+    fn piece() {
+        round_trip(Color::Black, r#""black""#);
+        round_trip(PieceKind::Knight, r#""knight""#);
+        round_trip(
+            Piece {
+                color: Color::Black,
+                kind: PieceKind::Queen,
+            },
+            r#""q""#,
+        );
+        assert!(serde_json::from_str::<Piece>(r#""x""#).is_err());
+    }
+
+    #[test]
+    fn moves() {
+        round_trip(Move::from_str("e7e8q").unwrap(), r#""e7e8q""#);
+
+        let p_move = MoveExt {
+            piece_kind: PieceKind::Pawn,
+            from: Square::from_str("e5").unwrap(),
+            to: Square::from_str("d6").unwrap(),
+            promotion: None,
+            capture: Some(Capture::EnPassant(Square::from_str("d5").unwrap())),
+        };
+        let json = serde_json::to_string(&p_move).unwrap();
+        assert_eq!(
+            json,
+            r#"{"piece_kind":"pawn","from":"e5","to":"d6","promotion":null,"capture":{"EnPassant":"d5"}}"#
+        );
+
+        let de: MoveExt = serde_json::from_str(&json).unwrap();
+        assert_eq!(de.to_move(), p_move.to_move());
+        assert_eq!(de.piece_kind, p_move.piece_kind);
+        assert!(de.is_en_passant());
+    }
+
+    #[test]
+    fn castling_rights() {
+        round_trip(CastlingRights::all(), r#""KQkq""#);
+        round_trip(CastlingRights::empty(), r#""-""#);
+        round_trip(
+            CastlingRights::WHITE_OOO | CastlingRights::BLACK_OO,
+            r#""Qk""#,
+        );
+        assert!(serde_json::from_str::<CastlingRights>(r#""KX""#).is_err());
+    }
+
+    #[cfg(feature = "fen")]
+    #[test]
+    fn position() {
+        round_trip(
+            crate::Position::starting(),
+            r#""rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1""#,
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! Polyglot opening book support.
+//!
+//! Polyglot books are a sorted list of 16 byte big-endian entries, each holding a position key,
+//! a move, a weight and a learn value. Positions are looked up using the Polyglot Zobrist key
+//! from [`polyglot_key`], which is unrelated to any other position hash.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use sealion_board::{Color, Move, PieceKind, Position, Square};
+
+mod random;
+
+pub use random::RANDOM64;
+
+const CASTLING_OFFSET: usize = 768;
+const EP_OFFSET: usize = 772;
+const TURN_OFFSET: usize = 780;
+
+/// Compute the Polyglot key of `position`.
+///
+/// The en passant file is only hashed when a pawn of the side to move can actually capture
+/// en passant.
+pub fn polyglot_key(position: &Position) -> u64 {
+    let mut key = 0;
+
+    for square in position.board.get_full_bb().set_iter() {
+        // `set_iter` only yields occupied squares
+        let piece = position.board.get(square).unwrap();
+        let kind = 2 * piece.kind as usize + usize::from(piece.color == Color::White);
+        key ^= RANDOM64[64 * kind + square.raw_index() as usize];
+    }
+
+    for (i, right) in [
+        position.castling.has_kingside(Color::White),
+        position.castling.has_queenside(Color::White),
+        position.castling.has_kingside(Color::Black),
+        position.castling.has_queenside(Color::Black),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        if right {
+            key ^= RANDOM64[CASTLING_OFFSET + i];
+        }
+    }
+
+    if let Some(ep_target) = position.ep_target {
+        if can_capture_ep(position, ep_target) {
+            key ^= RANDOM64[EP_OFFSET + ep_target.file() as usize];
+        }
+    }
+
+    if position.active_color == Color::White {
+        key ^= RANDOM64[TURN_OFFSET];
+    }
+
+    key
+}
+
+/// Check if a pawn of the side to move stands next to the pawn that just double pushed.
+fn can_capture_ep(position: &Position, ep_target: Square) -> bool {
+    let rank = match position.active_color {
+        Color::White => 4,
+        Color::Black => 3,
+    };
+
+    [
+        ep_target.file().checked_sub(1),
+        ep_target.file().checked_add(1),
+    ]
+    .into_iter()
+    .flatten()
+    .filter_map(|file| Square::at(rank, file))
+    .filter_map(|square| position.board.get(square))
+    .any(|piece| piece.kind == PieceKind::Pawn && piece.color == position.active_color)
+}
+
+/// A single book entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    pub key: u64,
+    /// The raw Polyglot move encoding.
+    pub raw_move: u16,
+    pub weight: u16,
+    pub learn: u32,
+}
+
+impl Entry {
+    /// Size of an encoded entry in bytes.
+    pub const SIZE: usize = 16;
+
+    /// Decode an entry from its big-endian representation.
+    pub fn from_bytes(bytes: [u8; Self::SIZE]) -> Self {
+        let [k0, k1, k2, k3, k4, k5, k6, k7, m0, m1, w0, w1, l0, l1, l2, l3] = bytes;
+
+        Self {
+            key: u64::from_be_bytes([k0, k1, k2, k3, k4, k5, k6, k7]),
+            raw_move: u16::from_be_bytes([m0, m1]),
+            weight: u16::from_be_bytes([w0, w1]),
+            learn: u32::from_be_bytes([l0, l1, l2, l3]),
+        }
+    }
+
+    /// Decode the move of this entry in `position`.
+    ///
+    /// Polyglot encodes castling as the king capturing its own rook, this is converted to the
+    /// usual king move.
+    pub fn to_move(&self, position: &Position) -> Option<Move> {
+        let square = |bits: u16| Square::at((bits >> 3 & 7) as u8, (bits & 7) as u8);
+
+        let from = square(self.raw_move >> 6)?;
+        let mut to = square(self.raw_move)?;
+
+        let promotion = match self.raw_move >> 12 & 7 {
+            0 => None,
+            1 => Some(PieceKind::Knight),
+            2 => Some(PieceKind::Bishop),
+            3 => Some(PieceKind::Rook),
+            4 => Some(PieceKind::Queen),
+            _ => return None,
+        };
+
+        let is_king = position.board.get_piece_kind(from) == Some(PieceKind::King);
+        if is_king && from.file() == 4 && to.rank() == from.rank() {
+            match to.file() {
+                0 => to = Square::at(to.rank(), 2)?,
+                7 => to = Square::at(to.rank(), 6)?,
+                _ => {}
+            }
+        }
+
+        Some(Move {
+            from,
+            to,
+            promotion,
+        })
+    }
+}
+
+/// A Polyglot opening book.
+#[derive(Debug, Clone, Default)]
+pub struct Book {
+    entries: Vec<Entry>,
+}
+
+impl Book {
+    /// Read a book from `reader`.
+    ///
+    /// Entries are expected to be sorted by key, as they are in any Polyglot book.
+    pub fn read(mut reader: impl Read) -> io::Result<Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        if bytes.len() % Entry::SIZE != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "book size is not a multiple of the entry size",
+            ));
+        }
+
+        let entries = bytes
+            .chunks_exact(Entry::SIZE)
+            // `chunks_exact` yields slices of exactly `Entry::SIZE` bytes
+            .map(|chunk| Entry::from_bytes(chunk.try_into().unwrap()))
+            .collect();
+
+        Ok(Self { entries })
+    }
+
+    /// Read a book from the file at `path`.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read(File::open(path)?)
+    }
+
+    /// All entries in this book.
+    #[inline]
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// The book moves for `position` with their weights.
+    pub fn probe(&self, position: &Position) -> Vec<(Move, u16)> {
+        let key = polyglot_key(position);
+        let start = self.entries.partition_point(|entry| entry.key < key);
+
+        self.entries[start..]
+            .iter()
+            .take_while(|entry| entry.key == key)
+            .filter_map(|entry| Some((entry.to_move(position)?, entry.weight)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEST_BOOK: &[u8] = include_bytes!("../data/test.bin");
+
+    fn mv(from: &str, to: &str) -> Move {
+        Move::new(from.parse().unwrap(), to.parse().unwrap())
+    }
+
+    #[test]
+    fn keys() {
+        // test positions from the polyglot book format description
+        for (fen, key) in [
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                0x463b96181691fc9c,
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+                0x823c9b50fd114196,
+            ),
+            (
+                "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2",
+                0x0756b94461c50fb0,
+            ),
+            (
+                "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2",
+                0x662fafb965db29d4,
+            ),
+            (
+                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+                0x22a48b5a8e47ff78,
+            ),
+            (
+                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 0 3",
+                0x652a607ca3f242c1,
+            ),
+            (
+                "rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 0 4",
+                0x00fdd303c946bdd9,
+            ),
+            (
+                "rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3",
+                0x3c8123ea7b067637,
+            ),
+            (
+                "rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 0 4",
+                0x5c3f9b829b279560,
+            ),
+        ] {
+            let position = sealion_fen::from_str(fen).unwrap();
+            assert_eq!(polyglot_key(&position), key, "{fen}");
+        }
+    }
+
+    #[test]
+    fn probe() {
+        let book = Book::read(TEST_BOOK).unwrap();
+        assert_eq!(book.entries().len(), 3);
+
+        assert_eq!(
+            book.probe(&Position::starting()),
+            vec![(mv("d2", "d4"), 5), (mv("e2", "e4"), 10)]
+        );
+
+        let castling = sealion_fen::from_str("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        assert_eq!(book.probe(&castling), vec![(mv("e1", "g1"), 1)]);
+
+        let missing = sealion_fen::from_str("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert!(book.probe(&missing).is_empty());
+
+        assert!(Book::read(&TEST_BOOK[1..]).is_err());
+    }
+}
@@ -1,6 +1,6 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 
-use sealion_board::{IntoEnumIterator, Piece, PieceKind, Position, Square};
+use sealion_board::{IntoEnumIterator, PieceKind, Position, Square};
 use sealion_engine::movegen::{Generator, MoveList};
 use sealion_engine::state::PositionState;
 
@@ -36,7 +36,25 @@ pub fn move_gen(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, piece_moves, move_gen);
+pub fn move_gen_into(c: &mut Criterion) {
+    let mut group = c.benchmark_group("movegen_into");
+    let mut buffer = Vec::with_capacity(256);
+
+    for (name, pos) in MOVE_GEN_POSITIONS {
+        let position = sealion_fen::from_str(pos).unwrap();
+        let state = PositionState::generate(&position);
+        let generator = Generator::new(&state);
+
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                generator.generate_into(black_box(&mut buffer));
+                black_box(&buffer);
+            })
+        });
+    }
+}
+
+criterion_group!(benches, piece_moves, move_gen, move_gen_into);
 criterion_main!(benches);
 
 const MOVE_GEN_POSITIONS: [(&str, &str); 4] = [
@@ -0,0 +1,34 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use sealion_engine::perft::perft;
+
+pub fn perft_positions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("perft");
+    group.sample_size(10);
+
+    for (name, pos, depth) in PERFT_POSITIONS {
+        let position = sealion_fen::from_str(pos).unwrap();
+
+        // report nodes per second
+        group.throughput(Throughput::Elements(perft(&position, depth) as u64));
+        group.bench_function(name, |b| {
+            b.iter(|| black_box(perft(black_box(&position), depth)));
+        });
+    }
+}
+
+criterion_group!(benches, perft_positions);
+criterion_main!(benches);
+
+const PERFT_POSITIONS: [(&str, &str, usize); 2] = [
+    (
+        "start_pos",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        4,
+    ),
+    (
+        "pos_5",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        3,
+    ),
+];
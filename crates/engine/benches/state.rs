@@ -10,6 +10,11 @@ pub fn pos_ext(c: &mut Criterion) {
         let start = Position::starting();
         b.iter(|| black_box(PositionState::generate(black_box(&start))));
     });
+
+    group.bench_function("StartPosMovegen", |b| {
+        let start = Position::starting();
+        b.iter(|| black_box(PositionState::generate_for_movegen(black_box(&start))));
+    });
 }
 
 criterion_group!(benches, pos_ext);
@@ -0,0 +1,386 @@
+//! Basic endgame knowledge.
+//!
+//! <https://www.chessprogramming.org/KPK>
+
+use std::sync::OnceLock;
+
+use sealion_board::{Color, PieceKind, Position, Square};
+
+use crate::movegen::Generator;
+
+// KPK bitbase entries
+const INVALID: u8 = 0;
+const UNKNOWN: u8 = 1;
+const DRAW: u8 = 2;
+const WIN: u8 = 4;
+
+/// Number of normalized KPK positions, with the pawn on files a-d and ranks 2-7.
+const KPK_SIZE: usize = 2 * 64 * 64 * 24;
+
+/// Index of a normalized KPK position, where the strong side is white.
+#[inline]
+fn kpk_index(stm: Color, strong_king: Square, weak_king: Square, pawn: Square) -> usize {
+    let pawn = (pawn.rank() as usize - 1) * 4 + pawn.file() as usize;
+    stm as usize
+        + 2 * (strong_king.raw_index() as usize + 64 * (weak_king.raw_index() as usize + 64 * pawn))
+}
+
+/// Classify a position without looking at its successors.
+fn kpk_initial(stm: Color, wk: Square, bk: Square, pawn: Square) -> u8 {
+    let pawn_attacks = Generator::pawn_attacks(pawn, Color::White);
+
+    if wk.distance(bk) <= 1
+        || wk == pawn
+        || bk == pawn
+        || (stm == Color::White && pawn_attacks.get(bk))
+    {
+        return INVALID;
+    }
+
+    if stm == Color::White && pawn.rank() == 6 {
+        let promotion = Square::from_index_unchecked(pawn.raw_index() + 8);
+
+        // promotes without being captured
+        if wk != promotion && (bk.distance(promotion) > 1 || wk.distance(promotion) == 1) {
+            return WIN;
+        }
+    }
+
+    if stm == Color::Black {
+        let escapes = Generator::king_attacks(bk) & !(Generator::king_attacks(wk) | pawn_attacks);
+
+        // stalemated, or the pawn can be captured
+        if escapes.is_empty() || escapes.get(pawn) && !Generator::king_attacks(wk).get(pawn) {
+            return DRAW;
+        }
+    }
+
+    UNKNOWN
+}
+
+/// Classify a position from the classification of its successors.
+fn kpk_classify(db: &[u8], stm: Color, wk: Square, bk: Square, pawn: Square) -> u8 {
+    let mut r = INVALID;
+
+    match stm {
+        Color::White => {
+            for to in Generator::king_attacks(wk).set_iter() {
+                r |= db[kpk_index(Color::Black, to, bk, pawn)];
+            }
+
+            if pawn.rank() < 6 {
+                let push = Square::from_index_unchecked(pawn.raw_index() + 8);
+                r |= db[kpk_index(Color::Black, wk, bk, push)];
+
+                let double_push = Square::from_index_unchecked(pawn.raw_index() + 16);
+                if pawn.rank() == 1 && push != wk && push != bk {
+                    r |= db[kpk_index(Color::Black, wk, bk, double_push)];
+                }
+            }
+        }
+        Color::Black => {
+            for to in Generator::king_attacks(bk).set_iter() {
+                r |= db[kpk_index(Color::White, wk, to, pawn)];
+            }
+        }
+    }
+
+    // the side to move picks the best successor
+    let (good, bad) = match stm {
+        Color::White => (WIN, DRAW),
+        Color::Black => (DRAW, WIN),
+    };
+
+    if r & good != 0 {
+        good
+    } else if r & UNKNOWN != 0 {
+        UNKNOWN
+    } else {
+        bad
+    }
+}
+
+fn kpk_positions() -> impl Iterator<Item = (Color, Square, Square, Square)> {
+    let pawns = (1..7).flat_map(|rank| (0..4).map(move |file| Square::at(rank, file).unwrap()));
+
+    pawns.flat_map(|pawn| {
+        (0..64).flat_map(move |bk| {
+            (0..64).flat_map(move |wk| {
+                [Color::White, Color::Black].map(|stm| {
+                    (
+                        stm,
+                        Square::from_index_unchecked(wk),
+                        Square::from_index_unchecked(bk),
+                        pawn,
+                    )
+                })
+            })
+        })
+    })
+}
+
+/// KPK bitbase, generated by retrograde analysis on first use.
+fn kpk_bitbase() -> &'static [u8] {
+    static BITBASE: OnceLock<Vec<u8>> = OnceLock::new();
+
+    BITBASE.get_or_init(|| {
+        let mut db = vec![INVALID; KPK_SIZE];
+
+        for (stm, wk, bk, pawn) in kpk_positions() {
+            db[kpk_index(stm, wk, bk, pawn)] = kpk_initial(stm, wk, bk, pawn);
+        }
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for (stm, wk, bk, pawn) in kpk_positions() {
+                let index = kpk_index(stm, wk, bk, pawn);
+
+                if db[index] == UNKNOWN {
+                    db[index] = kpk_classify(&db, stm, wk, bk, pawn);
+                    changed |= db[index] != UNKNOWN;
+                }
+            }
+        }
+
+        // anything left is a draw
+        for result in &mut db {
+            if *result == UNKNOWN {
+                *result = DRAW;
+            }
+        }
+
+        db
+    })
+}
+
+/// Count the pieces of `kind` and `color` on the board.
+#[inline]
+fn count(position: &Position, kind: PieceKind, color: Color) -> u32 {
+    let board = &position.board;
+    (board.get_piece_kind_bb(kind) & board.get_color_bb(color))
+        .0
+        .count_ones()
+}
+
+/// Check if the side with the pawn wins a king and pawn versus king endgame.
+///
+/// Returns `None` if the position is not a legal KPK position.
+pub fn is_kpk_win(position: &Position) -> Option<bool> {
+    let board = &position.board;
+
+    if board.get_full_bb().0.count_ones() != 3
+        || board.get_piece_kind_bb(PieceKind::Pawn).0.count_ones() != 1
+    {
+        return None;
+    }
+
+    let pawn_bb = board.get_piece_kind_bb(PieceKind::Pawn);
+    let strong = if (pawn_bb & board.get_color_bb(Color::White)).is_empty() {
+        Color::Black
+    } else {
+        Color::White
+    };
+
+    if count(position, PieceKind::King, strong) != 1
+        || count(position, PieceKind::King, !strong) != 1
+    {
+        return None;
+    }
+
+    let (mut wk, mut bk, mut pawn) = (
+        board.find_king(strong)?,
+        board.find_king(!strong)?,
+        pawn_bb.to_square()?,
+    );
+
+    // normalize so that white has the pawn, on files a-d
+    let flip = |square: &mut Square, mask: u8| {
+        *square = Square::from_index_unchecked(square.raw_index() ^ mask);
+    };
+    if strong == Color::Black {
+        for square in [&mut wk, &mut bk, &mut pawn] {
+            flip(square, 56);
+        }
+    }
+    if pawn.file() > 3 {
+        for square in [&mut wk, &mut bk, &mut pawn] {
+            flip(square, 7);
+        }
+    }
+
+    if pawn.rank() == 0 || pawn.rank() == 7 {
+        return None;
+    }
+
+    let stm = if position.active_color == strong {
+        Color::White
+    } else {
+        Color::Black
+    };
+
+    match kpk_bitbase()[kpk_index(stm, wk, bk, pawn)] {
+        WIN => Some(true),
+        DRAW => Some(false),
+        _ => None,
+    }
+}
+
+/// Check if neither side has enough material left to checkmate.
+///
+/// This is the case with only kings left, a single minor piece, or bishops that are all on
+/// squares of the same color.
+pub fn is_insufficient_material(position: &Position) -> bool {
+    let board = &position.board;
+
+    let heavy = board.get_piece_kind_bb(PieceKind::Pawn)
+        | board.get_piece_kind_bb(PieceKind::Rook)
+        | board.get_piece_kind_bb(PieceKind::Queen);
+    if !heavy.is_empty() {
+        return false;
+    }
+
+    let knights = board.get_piece_kind_bb(PieceKind::Knight);
+    let bishops = board.get_piece_kind_bb(PieceKind::Bishop);
+
+    match (knights | bishops).0.count_ones() {
+        0 | 1 => true,
+        _ if knights.is_empty() => {
+            bishops.set_iter().all(|square| square.is_light())
+                || bishops.set_iter().all(|square| square.is_dark())
+        }
+        _ => false,
+    }
+}
+
+/// Bonus for driving a lone king towards the corners with a king and queen or rook.
+///
+/// This rewards keeping the attacking king close to the lone king, which is needed to deliver
+/// mate. The bonus is relative to the side to move, i.e. negative if the side to move has the
+/// lone king, and is `None` if the position is not a KQvK or KRvK endgame.
+pub fn mating_bonus(position: &Position) -> Option<i16> {
+    let board = &position.board;
+
+    if board.get_full_bb().0.count_ones() != 3 {
+        return None;
+    }
+
+    let heavy =
+        board.get_piece_kind_bb(PieceKind::Queen) | board.get_piece_kind_bb(PieceKind::Rook);
+    let heavy = heavy.to_square()?;
+    let strong = board.get_color(heavy)?;
+
+    let strong_king = board.find_king(strong)?;
+    let weak_king = board.find_king(!strong)?;
+
+    let bonus = push_to_edge(weak_king) + push_close(strong_king, weak_king);
+
+    Some(if strong == position.active_color {
+        bonus
+    } else {
+        -bonus
+    })
+}
+
+/// Bonus for a king being far away from the center.
+#[inline]
+fn push_to_edge(square: Square) -> i16 {
+    let rank = square.rank().max(7 - square.rank()) - 4;
+    let file = square.file().max(7 - square.file()) - 4;
+    20 * i16::from(rank + file)
+}
+
+/// Bonus for two kings being close together.
+#[inline]
+fn push_close(a: Square, b: Square) -> i16 {
+    10 * (7 - i16::from(a.distance(b)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::eval::evaluate;
+
+    fn kpk(fen: &str) -> Option<bool> {
+        is_kpk_win(&sealion_fen::from_str(fen).unwrap())
+    }
+
+    #[test]
+    fn kpk_win() {
+        // king on the sixth rank in front of the pawn
+        assert_eq!(kpk("4k3/8/4K3/4P3/8/8/8/8 b - - 0 1"), Some(true));
+        assert_eq!(kpk("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1"), Some(true));
+        // defending king can't catch the pawn
+        assert_eq!(kpk("8/k7/8/8/8/8/6P1/6K1 w - - 0 1"), Some(true));
+        // mirrored for black
+        assert_eq!(kpk("8/8/8/8/4p3/4k3/8/4K3 w - - 0 1"), Some(true));
+    }
+
+    #[test]
+    fn kpk_draw() {
+        // defending king has the opposition
+        assert_eq!(kpk("8/4k3/8/4K3/4P3/8/8/8 w - - 0 1"), Some(false));
+        // stalemate
+        assert_eq!(kpk("4k3/4P3/4K3/8/8/8/8/8 b - - 0 1"), Some(false));
+        // rook pawn with the defending king in the corner
+        assert_eq!(kpk("k7/8/8/8/P7/8/8/1K6 w - - 0 1"), Some(false));
+        // pawn falls
+        assert_eq!(kpk("8/8/8/3k4/4P3/8/8/K7 b - - 0 1"), Some(false));
+    }
+
+    #[test]
+    fn not_kpk() {
+        assert_eq!(
+            kpk("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"),
+            None
+        );
+        assert_eq!(kpk("4k3/8/4K3/4R3/8/8/8/8 w - - 0 1"), None);
+        assert_eq!(kpk("4k3/4p3/4K3/4P3/8/8/8/8 w - - 0 1"), None);
+    }
+
+    #[test]
+    fn insufficient_material() {
+        let insufficient = |fen| is_insufficient_material(&sealion_fen::from_str(fen).unwrap());
+
+        assert!(insufficient("4k3/8/8/8/8/8/8/4K3 w - - 0 1"));
+        assert!(insufficient("4k3/8/8/8/8/8/8/1N2K3 w - - 0 1"));
+        assert!(insufficient("4k3/8/8/8/8/8/8/2b1K3 w - - 0 1"));
+        // bishops on dark squares only
+        assert!(insufficient("4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1"));
+
+        assert!(!insufficient("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1"));
+        assert!(!insufficient("4k3/8/8/8/8/8/8/1NN1K3 w - - 0 1"));
+        assert!(!insufficient("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"));
+        assert!(!insufficient(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+        ));
+    }
+
+    #[test]
+    fn mating_bonus_krk() {
+        let bonus = |fen| evaluate(&sealion_fen::from_str(fen).unwrap());
+
+        let center = bonus("8/8/8/3k4/8/8/8/R3K3 w - - 0 1");
+        let edge = bonus("3k4/8/8/8/8/8/8/R3K3 w - - 0 1");
+        let corner = bonus("k7/8/8/8/8/8/8/R3K3 w - - 0 1");
+        assert!(center < edge);
+        assert!(edge < corner);
+
+        // kings closer together
+        let close = bonus("k7/8/1K6/8/8/8/8/R7 w - - 0 1");
+        assert!(corner < close);
+
+        // the same bonus against the side to move
+        assert_eq!(bonus("k7/8/1K6/8/8/8/8/R7 b - - 0 1"), -close);
+        assert!(
+            mating_bonus(&sealion_fen::from_str("k7/8/1K6/8/8/8/8/R7 b - - 0 1").unwrap())
+                < Some(0)
+        );
+
+        assert_eq!(
+            mating_bonus(&sealion_fen::from_str("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1").unwrap()),
+            None
+        );
+    }
+}
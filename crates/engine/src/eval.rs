@@ -0,0 +1,374 @@
+//! Static evaluation.
+//!
+//! <https://www.chessprogramming.org/Evaluation>
+
+use std::cmp::Ordering;
+
+use sealion_board::bitboard::constants::*;
+use sealion_board::{BitBoard, Board, Color, IntoEnumIterator, Piece, PieceKind, Position};
+
+use crate::endgame::mating_bonus;
+
+/// Game phase of the starting position, see [`game_phase`].
+pub const MAX_PHASE: i32 = 24;
+
+/// King placement in the middlegame, from white's point of view with rank 8 at the top.
+#[rustfmt::skip]
+const KING_MG: [i16; 64] = [
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -20, -30, -30, -40, -40, -30, -30, -20,
+    -10, -20, -20, -20, -20, -20, -20, -10,
+     20,  20,   0,   0,   0,   0,  20,  20,
+     20,  30,  10,   0,   0,  10,  30,  20,
+];
+
+/// King placement in the endgame, see [`KING_MG`].
+#[rustfmt::skip]
+const KING_EG: [i16; 64] = [
+    -50, -40, -30, -20, -20, -30, -40, -50,
+    -30, -20, -10,   0,   0, -10, -20, -30,
+    -30, -10,  20,  30,  30,  20, -10, -30,
+    -30, -10,  30,  40,  40,  30, -10, -30,
+    -30, -10,  30,  40,  40,  30, -10, -30,
+    -30, -10,  20,  30,  30,  20, -10, -30,
+    -30, -30,   0,   0,   0,   0, -30, -30,
+    -50, -30, -30, -30, -30, -30, -30, -50,
+];
+
+/// Search score, relative to the side to move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Score {
+    /// Evaluation in centipawns.
+    CentiPawn(i32),
+    /// The side to move mates in the given number of plies.
+    Mate(u32),
+    /// The side to move gets mated in the given number of plies.
+    ///
+    /// A checkmated position is mated in 0.
+    Mated(u32),
+}
+
+impl Score {
+    /// The side to move mates in `plies`.
+    #[inline]
+    pub const fn mate_in(plies: u32) -> Self {
+        Self::Mate(plies)
+    }
+
+    /// The side to move gets mated in `plies`.
+    #[inline]
+    pub const fn mated_in(plies: u32) -> Self {
+        Self::Mated(plies)
+    }
+
+    /// Convert to centipawns, with mates scored as `mate_value` minus the distance to mate.
+    ///
+    /// `mate_value` should be larger than any centipawn score for the ordering to hold.
+    #[inline]
+    pub const fn as_centipawns_with_mate(&self, mate_value: i32) -> i32 {
+        match *self {
+            Self::CentiPawn(cp) => cp,
+            Self::Mate(plies) => mate_value - plies as i32,
+            Self::Mated(plies) => -mate_value + plies as i32,
+        }
+    }
+
+    /// Sort key where mating dominates any centipawn score, which dominates getting mated.
+    #[inline]
+    fn key(&self) -> (i8, i64) {
+        match *self {
+            Self::CentiPawn(cp) => (0, cp as i64),
+            // faster mates are better, and slower mates against us
+            Self::Mate(plies) => (1, -(plies as i64)),
+            Self::Mated(plies) => (-1, plies as i64),
+        }
+    }
+}
+
+impl PartialOrd for Score {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Score {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+const DOUBLED_PENALTY: i16 = 15;
+const ISOLATED_PENALTY: i16 = 10;
+/// Passed pawn bonus by rank, from the point of view of the pawn's color.
+const PASSED_BONUS: [i16; 8] = [0, 5, 10, 20, 35, 60, 100, 0];
+
+/// Pawn structure of one side.
+///
+/// <https://www.chessprogramming.org/Pawn_Structure>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PawnInfo {
+    /// Pawns with another friendly pawn behind them on the same file.
+    pub doubled: BitBoard,
+    /// Pawns without friendly pawns on the adjacent files.
+    pub isolated: BitBoard,
+    /// Pawns without opposing pawns in front of them on the same or adjacent files.
+    pub passed: BitBoard,
+}
+
+impl PawnInfo {
+    /// Score of this pawn structure for the side it was computed for.
+    pub fn score(&self, color: Color) -> i16 {
+        let mut score = -DOUBLED_PENALTY * self.doubled.0.count_ones() as i16
+            - ISOLATED_PENALTY * self.isolated.0.count_ones() as i16;
+
+        for square in self.passed.set_iter() {
+            let rank = match color {
+                Color::White => square.rank(),
+                Color::Black => 7 - square.rank(),
+            };
+            score += PASSED_BONUS[rank as usize];
+        }
+
+        score
+    }
+}
+
+/// Fill all squares in front of the set squares, from the point of view of `color`.
+#[inline]
+fn front_fill(bb: BitBoard, color: Color) -> BitBoard {
+    let mut x = bb.0;
+
+    match color {
+        Color::White => {
+            x |= x << 8;
+            x |= x << 16;
+            x |= x << 32;
+        }
+        Color::Black => {
+            x |= x >> 8;
+            x |= x >> 16;
+            x |= x >> 32;
+        }
+    }
+
+    BitBoard(x)
+}
+
+/// Find the doubled, isolated and passed pawns of `color`.
+pub fn pawn_structure(board: &Board, color: Color) -> PawnInfo {
+    let pawns = board.get_piece_bb(Piece {
+        color,
+        kind: PieceKind::Pawn,
+    });
+    let their_pawns = board.get_piece_bb(Piece {
+        color: !color,
+        kind: PieceKind::Pawn,
+    });
+
+    // squares in front of any friendly pawn
+    let ahead = match color {
+        Color::White => front_fill(pawns, color) << 8,
+        Color::Black => front_fill(pawns, color) >> 8,
+    };
+
+    let mut info = PawnInfo {
+        doubled: pawns & ahead,
+        ..Default::default()
+    };
+
+    for square in pawns.set_iter() {
+        if (square.file_neighbors() & pawns).is_empty() {
+            info.isolated |= BitBoard::from_square(square);
+        }
+    }
+
+    for square in pawns.set_iter() {
+        let square_bb = BitBoard::from_square(square);
+        let front = front_fill(square_bb, color) & !square_bb;
+        let span = front | ((front << 1) & !A_FILE) | ((front >> 1) & !H_FILE);
+
+        if (span & their_pawns).is_empty() {
+            info.passed |= square_bb;
+        }
+    }
+
+    info
+}
+
+/// Remaining non-pawn material, from [`MAX_PHASE`] in the opening down to 0 in a pawn endgame.
+///
+/// <https://www.chessprogramming.org/Game_Phase>
+pub fn game_phase(board: &Board) -> i32 {
+    let phase: i32 = [
+        (PieceKind::Knight, 1),
+        (PieceKind::Bishop, 1),
+        (PieceKind::Rook, 2),
+        (PieceKind::Queen, 4),
+    ]
+    .into_iter()
+    .map(|(kind, weight)| board.get_piece_kind_bb(kind).0.count_ones() as i32 * weight)
+    .sum();
+
+    // promotions can add material past the starting position
+    phase.min(MAX_PHASE)
+}
+
+/// Middlegame and endgame score of the king of `color`.
+#[inline]
+fn king_score(board: &Board, color: Color) -> (i16, i16) {
+    match board.find_king(color) {
+        Some(square) => {
+            // tables have rank 8 first
+            let index = match color {
+                Color::White => square.raw_index() ^ 56,
+                Color::Black => square.raw_index(),
+            } as usize;
+            (KING_MG[index], KING_EG[index])
+        }
+        None => (0, 0),
+    }
+}
+
+/// Static evaluation of `position`, relative to the side to move.
+///
+/// Positive scores are good for the side to move, as with [`PseudoScore`]. Middlegame and
+/// endgame scores are interpolated by the [`game_phase`]. KQvK and KRvK endgames get the
+/// [`mating_bonus`] on top, so the search drives the lone king to the corner.
+///
+/// [`PseudoScore`]: crate::state::PseudoScore
+pub fn evaluate(position: &Position) -> i16 {
+    let board = &position.board;
+    let us = position.active_color;
+
+    let mut score = 0;
+
+    for kind in PieceKind::iter().filter(|kind| *kind != PieceKind::King) {
+        let count = |color| board.get_piece_bb(Piece { color, kind }).0.count_ones() as i16;
+        score += kind.score() * (count(us) - count(!us));
+    }
+
+    score += pawn_structure(board, us).score(us);
+    score -= pawn_structure(board, !us).score(!us);
+
+    let (our_mg, our_eg) = king_score(board, us);
+    let (their_mg, their_eg) = king_score(board, !us);
+    let mg = i32::from(our_mg - their_mg);
+    let eg = i32::from(our_eg - their_eg);
+
+    let phase = game_phase(board);
+    let tapered = (mg * phase + eg * (MAX_PHASE - phase)) / MAX_PHASE;
+
+    score + tapered as i16 + mating_bonus(position).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use sealion_board::Square;
+
+    use super::*;
+
+    fn squares(squares: &[&str]) -> BitBoard {
+        squares.iter().fold(BitBoard::ZERO, |bb, s| {
+            bb | BitBoard::from_square(Square::from_str(s).unwrap())
+        })
+    }
+
+    fn pawns(fen: &str, color: Color) -> PawnInfo {
+        pawn_structure(&sealion_fen::from_str(fen).unwrap().board, color)
+    }
+
+    #[test]
+    fn score_ordering() {
+        for cp in [i32::MIN, -300, 0, 300, i32::MAX] {
+            assert!(Score::mate_in(1) > Score::CentiPawn(cp));
+            assert!(Score::mated_in(1) < Score::CentiPawn(cp));
+        }
+
+        assert!(Score::mate_in(1) > Score::mate_in(3));
+        assert!(Score::mated_in(1) < Score::mated_in(3));
+        assert!(Score::mated_in(1) < Score::mate_in(1));
+        assert!(Score::mated_in(0) < Score::CentiPawn(i32::MIN));
+        assert!(Score::mated_in(0) < Score::mated_in(1));
+        assert!(Score::mate_in(0) > Score::mate_in(1));
+        assert!(Score::CentiPawn(10) > Score::CentiPawn(-10));
+
+        let mate = 30_000;
+        assert_eq!(Score::mate_in(3).as_centipawns_with_mate(mate), 29_997);
+        assert_eq!(Score::mated_in(3).as_centipawns_with_mate(mate), -29_997);
+        assert_eq!(Score::mated_in(0).as_centipawns_with_mate(mate), -30_000);
+        assert_eq!(Score::CentiPawn(42).as_centipawns_with_mate(mate), 42);
+    }
+
+    #[test]
+    fn doubled() {
+        let info = pawns("4k3/8/8/8/2P5/2P5/2P2P2/4K3 w - - 0 1", Color::White);
+        assert_eq!(info.doubled, squares(&["c3", "c4"]));
+
+        let info = pawns("4k3/2p5/2p5/8/8/8/8/4K3 w - - 0 1", Color::Black);
+        assert_eq!(info.doubled, squares(&["c6"]));
+    }
+
+    #[test]
+    fn isolated() {
+        let info = pawns("4k3/8/8/8/8/8/P1P1P1PP/4K3 w - - 0 1", Color::White);
+        assert_eq!(info.isolated, squares(&["a2", "c2", "e2"]));
+    }
+
+    #[test]
+    fn passed() {
+        // the d-pawn is blocked, and the e-pawn is guarded against by the d-pawn
+        let fen = "4k3/8/3p4/8/3P1P2/P3P3/8/4K3 w - - 0 1";
+        let info = pawns(fen, Color::White);
+        assert_eq!(info.passed, squares(&["a3", "f4"]));
+
+        let info = pawns(fen, Color::Black);
+        assert_eq!(info.passed, BitBoard::ZERO);
+    }
+
+    #[test]
+    fn phase() {
+        assert_eq!(game_phase(&Position::starting().board), MAX_PHASE);
+
+        let position = sealion_fen::from_str("4k3/pppp4/8/8/8/8/PPPP4/4K3 w - - 0 1").unwrap();
+        assert_eq!(game_phase(&position.board), 0);
+
+        let position = sealion_fen::from_str("3qk3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        assert_eq!(game_phase(&position.board), 6);
+    }
+
+    #[test]
+    fn tapered_king() {
+        let eval = |fen| evaluate(&sealion_fen::from_str(fen).unwrap());
+
+        // the king belongs in the center in a pawn endgame
+        let back_rank = eval("4k3/pppp4/8/8/8/8/PPPP4/4K3 w - - 0 1");
+        let center = eval("4k3/pppp4/8/8/4K3/8/PPPP4/8 w - - 0 1");
+        assert!(center > back_rank);
+
+        // but not in the opening
+        let back_rank = eval("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        let center = eval("rnbqkbnr/pppppppp/8/8/4K3/8/PPPPPPPP/RNBQ1BNR w kq - 0 1");
+        assert!(center < back_rank);
+    }
+
+    #[test]
+    fn evaluate_symmetric() {
+        let start = Position::starting();
+        assert_eq!(evaluate(&start), 0);
+
+        // white has a passed pawn
+        let position = sealion_fen::from_str("4k3/8/8/3P4/8/8/8/4K3 w - - 0 1").unwrap();
+        let white = evaluate(&position);
+        assert!(white > 100);
+
+        let position = sealion_fen::from_str("4k3/8/8/3P4/8/8/8/4K3 b - - 0 1").unwrap();
+        assert_eq!(evaluate(&position), -white);
+    }
+}
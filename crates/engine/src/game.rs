@@ -0,0 +1,196 @@
+//! Game state across moves, and game results.
+
+use sealion_board::{Color, Move, MoveExt, Position};
+
+use crate::endgame;
+use crate::movegen::MoveList;
+use crate::position::{MoveError, PositionExt};
+use crate::state::PositionState;
+
+/// Why a game was drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DrawReason {
+    /// The side to move has no legal moves, but is not in check.
+    Stalemate,
+    /// No capture or pawn move in the last 50 moves.
+    FiftyMove,
+    /// The same position occurred three times.
+    Repetition,
+    /// Neither side has enough material left to checkmate.
+    InsufficientMaterial,
+    /// Both players agreed to a draw, never detected from the position.
+    Agreement,
+}
+
+/// Result of a finished game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    WhiteWins,
+    BlackWins,
+    Draw(DrawReason),
+}
+
+impl Outcome {
+    /// The outcome where `color` wins.
+    #[inline]
+    pub const fn win(color: Color) -> Self {
+        match color {
+            Color::White => Self::WhiteWins,
+            Color::Black => Self::BlackWins,
+        }
+    }
+}
+
+/// Positions of a game so far, starting from the initial position.
+#[derive(Debug, Clone)]
+pub struct GameHistory {
+    positions: Vec<Position>,
+}
+
+impl GameHistory {
+    #[inline]
+    pub fn new(position: Position) -> Self {
+        Self {
+            positions: vec![position],
+        }
+    }
+
+    /// The current position.
+    #[inline]
+    pub fn current(&self) -> &Position {
+        // never empty
+        self.positions.last().unwrap()
+    }
+
+    /// All positions of the game, starting from the initial position.
+    #[inline]
+    pub fn positions(&self) -> &[Position] {
+        &self.positions
+    }
+
+    /// Play a move if it is legal in the current position.
+    pub fn play(&mut self, p_move: Move) -> Result<MoveExt, MoveError> {
+        let mut position = self.current().clone();
+        let p_move = position.apply_move(p_move)?;
+
+        self.positions.push(position);
+        Ok(p_move)
+    }
+
+    /// Number of times the current position occurred, including itself.
+    ///
+    /// Only positions since the last capture or pawn move are considered, since earlier ones
+    /// can't be repeated.
+    pub fn repetitions(&self) -> usize {
+        let current = self.current();
+        let reversible = (current.halfmove_clock as usize + 1).min(self.positions.len());
+
+        self.positions[self.positions.len() - reversible..]
+            .iter()
+            .filter(|position| position.same_position(current))
+            .count()
+    }
+
+    /// The result of the game, or `None` if it is still going.
+    ///
+    /// Checkmate takes precedence over the draw rules, which are applied as soon as they can
+    /// be claimed.
+    pub fn outcome(&self) -> Option<Outcome> {
+        let position = self.current();
+
+        match MoveList::generate(&PositionState::generate(position)) {
+            MoveList::Checkmate => return Some(Outcome::win(!position.active_color)),
+            MoveList::Stalemate => return Some(Outcome::Draw(DrawReason::Stalemate)),
+            MoveList::Moves(_) => {}
+        }
+
+        if self.repetitions() >= 3 {
+            Some(Outcome::Draw(DrawReason::Repetition))
+        } else if position.is_fifty_move_draw() {
+            Some(Outcome::Draw(DrawReason::FiftyMove))
+        } else if endgame::is_insufficient_material(position) {
+            Some(Outcome::Draw(DrawReason::InsufficientMaterial))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn play(fen: &str, lans: &[&str]) -> GameHistory {
+        let mut game = GameHistory::new(sealion_fen::from_str(fen).unwrap());
+        for lan in lans {
+            game.play(Move::from_str(lan).unwrap()).unwrap();
+        }
+        game
+    }
+
+    const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    #[test]
+    fn checkmate() {
+        let game = play(START, &["f2f3", "e7e5", "g2g4", "d8h4"]);
+        assert_eq!(game.outcome(), Some(Outcome::BlackWins));
+
+        let game = play(START, &["f2f3", "e7e5", "g2g4"]);
+        assert_eq!(game.outcome(), None);
+    }
+
+    #[test]
+    fn stalemate() {
+        let game = play("4k3/4P3/3K4/8/8/8/8/8 w - - 0 1", &[]);
+        assert_eq!(game.outcome(), None);
+
+        let game = play("4k3/4P3/3K4/8/8/8/8/8 w - - 0 1", &["d6e6"]);
+        assert_eq!(game.outcome(), Some(Outcome::Draw(DrawReason::Stalemate)));
+    }
+
+    #[test]
+    fn fifty_move() {
+        const FEN: &str = "4k3/8/8/8/8/8/4P3/4K1N1 w - - 99 80";
+
+        assert_eq!(play(FEN, &[]).outcome(), None);
+        assert_eq!(
+            play(FEN, &["g1f3"]).outcome(),
+            Some(Outcome::Draw(DrawReason::FiftyMove))
+        );
+        // the pawn move resets the clock just in time
+        assert_eq!(play(FEN, &["e2e4"]).outcome(), None);
+    }
+
+    #[test]
+    fn repetition() {
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+
+        let game = play(START, &shuffle);
+        assert_eq!(game.repetitions(), 2);
+        assert_eq!(game.outcome(), None);
+
+        let game = play(START, &[shuffle, shuffle].concat());
+        assert_eq!(game.repetitions(), 3);
+        assert_eq!(game.outcome(), Some(Outcome::Draw(DrawReason::Repetition)));
+
+        // positions before a pawn move can't be repeated
+        let black = ["g8f6", "g1f3", "f6g8", "f3g1"];
+        let game = play(START, &[&shuffle[..], &["e2e4"], &black[..]].concat());
+        assert_eq!(game.repetitions(), 2);
+        assert_eq!(game.outcome(), None);
+    }
+
+    #[test]
+    fn insufficient_material() {
+        let game = play("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1", &[]);
+        assert_eq!(
+            game.outcome(),
+            Some(Outcome::Draw(DrawReason::InsufficientMaterial))
+        );
+
+        let game = play("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1", &[]);
+        assert_eq!(game.outcome(), None);
+    }
+}
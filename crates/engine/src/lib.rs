@@ -1,4 +1,20 @@
 #![allow(clippy::comparison_chain)]
 
+pub mod endgame;
+pub mod eval;
+pub mod game;
 pub mod movegen;
+pub mod ordering;
+pub mod perft;
+pub mod position;
+pub mod prelude;
+#[cfg(feature = "rand")]
+pub mod random;
 pub mod state;
+pub mod time;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use eval::Score;
+pub use position::{Analysis, MoveError, PositionExt};
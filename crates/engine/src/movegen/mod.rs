@@ -1,9 +1,10 @@
 //! Move generator implementation.
 
 use std::cmp::min;
-use std::ops::BitOr;
+use std::fmt::Display;
+use std::ops::{BitOr, ControlFlow};
 
-use sealion_board::{BitBoard, CastlingRights, Color, MoveExt, PieceKind, Square};
+use sealion_board::{BitBoard, Board, Color, Move, MoveExt, PieceKind, Position, Square};
 use smallvec::SmallVec;
 
 use crate::state::PositionState;
@@ -16,6 +17,61 @@ pub fn merge_bb<const U: usize>(boards: [BitBoard; U]) -> BitBoard {
     boards.into_iter().fold(BitBoard::ZERO, BitOr::bitor)
 }
 
+/// All squares attacked by the pieces of `color`.
+///
+/// Unlike [`Attacks::bb`](crate::state::Attacks::bb), sliding attacks stop at the opposing
+/// king instead of passing through it, and no pin or check information is gathered.
+pub fn attacks_by(board: &Board, color: Color) -> BitBoard {
+    let blockers = board.get_full_bb();
+    let mut attacks = BitBoard::ZERO;
+
+    for square in board.get_color_bb(color).set_iter() {
+        let kind = match board.get_piece_kind(square) {
+            Some(kind) => kind,
+            None => continue,
+        };
+
+        attacks |= match kind {
+            Pawn => Generator::pawn_attacks(square, color),
+            Knight => Generator::knight_attacks(square),
+            Bishop => merge_bb(Generator::sliding_attacks::<0>(square, blockers)),
+            Rook => merge_bb(Generator::sliding_attacks::<1>(square, blockers)),
+            Queen => {
+                merge_bb(Generator::sliding_attacks::<0>(square, blockers))
+                    | merge_bb(Generator::sliding_attacks::<1>(square, blockers))
+            }
+            King => Generator::king_attacks(square),
+        };
+    }
+
+    attacks
+}
+
+/// All pieces of either color attacking `square`, as if only the pieces in `occupancy` were on
+/// the board.
+///
+/// Pieces outside of `occupancy` neither attack nor block, so removing pieces from it reveals
+/// the sliders behind them, e.g. for static exchange evaluation.
+pub fn attackers_to(board: &Board, square: Square, occupancy: BitBoard) -> BitBoard {
+    let pawns = board.get_piece_kind_bb(Pawn);
+    let queens = board.get_piece_kind_bb(Queen);
+    let diagonal = board.get_piece_kind_bb(Bishop) | queens;
+    let straight = board.get_piece_kind_bb(Rook) | queens;
+
+    let white_pawns = pawns & board.get_color_bb(Color::White);
+    let black_pawns = pawns & board.get_color_bb(Color::Black);
+
+    // pawns attack in reverse from the square
+    let attackers = (Generator::pawn_attacks(square, Color::White) & black_pawns)
+        | (Generator::pawn_attacks(square, Color::Black) & white_pawns)
+        | (Generator::knight_attacks(square) & board.get_piece_kind_bb(Knight))
+        | (Generator::king_attacks(square) & board.get_piece_kind_bb(King))
+        | (merge_bb(Generator::sliding_attacks::<0>(square, occupancy)) & diagonal)
+        | (merge_bb(Generator::sliding_attacks::<1>(square, occupancy)) & straight);
+
+    attackers & occupancy
+}
+
 /// The primary structure which contains relevant piece state information, such as attacks and checks.
 #[derive(Debug, Clone)]
 pub enum MoveList {
@@ -26,11 +82,80 @@ pub enum MoveList {
 
 impl MoveList {
     /// Generate the full [`MoveList`] without going through an intermediate generator.
+    ///
+    /// The side to move should have exactly one king. Without one, moves are still generated
+    /// for the remaining pieces, but the position can never be reported as checkmate. See
+    /// [`Generator::try_new`] to reject such positions up front.
     #[inline]
     pub fn generate(position: &PositionState) -> Self {
         let generator = Generator::new(position);
         generator.generate()
     }
+
+    /// Generate the [`MoveList`] of `position`, along with the state it was generated from.
+    ///
+    /// The state holds the opponent attacks, checkers and pins, which can be re-used after
+    /// generating moves instead of recomputing them.
+    #[inline]
+    pub fn generate_with_state(position: &Position) -> (Self, PositionState<'_>) {
+        let state = PositionState::generate(position);
+        (Self::generate(&state), state)
+    }
+
+    /// Check if `p_move` is one of the moves, always false for checkmate and stalemate.
+    #[inline]
+    pub fn contains(&self, p_move: &Move) -> bool {
+        match self {
+            Self::Moves(moves) => moves.iter().any(|m| m.to_move() == *p_move),
+            Self::Checkmate | Self::Stalemate => false,
+        }
+    }
+}
+
+impl Display for MoveList {
+    /// Summarize the moves, e.g. `2 legal moves: e1d1 e1f1`.
+    ///
+    /// Moves are listed in long algebraic notation, sorted alphabetically.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Checkmate => write!(f, "Checkmate"),
+            Self::Stalemate => write!(f, "Stalemate"),
+            Self::Moves(moves) => {
+                let mut lans: Vec<_> = moves.iter().map(|m| m.to_move().to_string()).collect();
+                lans.sort_unstable();
+
+                let plural = if moves.len() == 1 { "" } else { "s" };
+                write!(f, "{} legal move{plural}: {}", moves.len(), lans.join(" "))
+            }
+        }
+    }
+}
+
+/// Special moves produced by a [`Generator`].
+///
+/// Everything is generated by default. Disabling some moves is useful for callers that only
+/// look at regular piece moves, such as exchange evaluation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GenFlags {
+    pub castling: bool,
+    pub en_passant: bool,
+    pub promotions: bool,
+}
+
+impl GenFlags {
+    /// Generate all moves.
+    pub const ALL: Self = Self {
+        castling: true,
+        en_passant: true,
+        promotions: true,
+    };
+}
+
+impl Default for GenFlags {
+    #[inline]
+    fn default() -> Self {
+        Self::ALL
+    }
 }
 
 /// Move generator re-usable data.
@@ -38,22 +163,48 @@ impl MoveList {
 pub struct Generator<'a> {
     /// The position we are generating moves for.
     state: &'a PositionState<'a>,
+    /// Special moves to generate.
+    flags: GenFlags,
 }
 
 impl<'a> Generator<'a> {
     #[inline]
     pub fn new(state: &'a PositionState) -> Self {
-        Self { state }
+        Self {
+            state,
+            flags: GenFlags::ALL,
+        }
+    }
+
+    /// Only generate the special moves enabled in `flags`.
+    ///
+    /// With any moves disabled, an empty move list no longer means that the position is mate,
+    /// so [`generate`](Self::generate) always returns [`MoveList::Moves`].
+    #[inline]
+    pub fn with_flags(mut self, flags: GenFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Create a generator only if the side to move has exactly one king.
+    #[inline]
+    pub fn try_new(state: &'a PositionState) -> Option<Self> {
+        let position = state.position;
+
+        position
+            .board
+            .find_king(position.active_color)
+            .map(|_| Self::new(state))
     }
 
     pub fn generate(&self) -> MoveList {
         let move_list = self.generate_impl();
 
-        if move_list.is_empty() {
-            if self.state.attacks.bb & self.state.board_ext.king_bb != 0 {
-                return MoveList::Checkmate;
+        if move_list.is_empty() && self.flags == GenFlags::ALL {
+            if self.state.attacks.checkers.is_empty() {
+                return MoveList::Stalemate;
             }
-            return MoveList::Stalemate;
+            return MoveList::Checkmate;
         }
 
         MoveList::Moves(move_list)
@@ -61,68 +212,100 @@ impl<'a> Generator<'a> {
 
     fn generate_impl(&self) -> Vec<MoveExt> {
         let mut moves = Vec::with_capacity(256);
+        self.generate_into(&mut moves);
+        moves
+    }
 
-        // initial king move generation
-        let king_sq = self.state.board_ext.king_bb.to_square_unchecked();
-        let king_moves = self.pseudo_king_moves(king_sq) & !self.state.attacks.bb;
-
-        for to_square in king_moves.set_iter() {
-            let p_move = MoveExt {
-                from: king_sq,
-                to: to_square,
-                piece_kind: King,
-                promotion: None,
-                capture: self.state.resolve_capture_only(to_square),
-            };
+    /// Generate all legal moves into `moves`, clearing it first.
+    ///
+    /// Re-using the same buffer across positions avoids allocating a new move list each time.
+    pub fn generate_into(&self, moves: &mut Vec<MoveExt>) {
+        moves.clear();
 
+        let _ = self.for_each_move(|p_move| {
             moves.push(p_move);
-        }
+            ControlFlow::Continue(())
+        });
+    }
 
-        // Double check
-        // - Forced king move
-        if self.state.attacks.checkers.melee.len() + self.state.attacks.checkers.sliders.len() > 1 {
-            return moves;
-        }
+    /// Generate all legal moves, invoking `f` on each one as it is generated.
+    ///
+    /// Generation stops early if `f` returns [`ControlFlow::Break`], which is then returned.
+    /// This avoids allocating a full move list when only some of the moves are needed.
+    #[inline]
+    pub fn for_each_move<F>(&self, f: F) -> ControlFlow<()>
+    where
+        F: FnMut(MoveExt) -> ControlFlow<()>,
+    {
+        self.for_each_move_impl::<false, F>(f)
+    }
 
-        let mut restricted = BitBoard(u64::MAX);
+    /// Generate only the moves out of check into `moves`, clearing it first.
+    ///
+    /// These are the same as the legal moves, but only pieces that can capture the checker or
+    /// block the check are looked at. Nothing is generated if the side to move is not in check.
+    pub fn generate_evasions(&self, moves: &mut Vec<MoveExt>) {
+        moves.clear();
 
-        // Melee check
-        // - Checker can be captured
-        // ~ King move to non-attacked square
-        if let Some(checker_sq) = self.state.attacks.checkers.melee.get(0) {
-            restricted = BitBoard::from_square(*checker_sq);
+        if self.state.attacks.checkers.is_empty() {
+            return;
         }
 
-        // Sliding check
-        // - Checker can be captured
-        // - Checker can be blocked along attack-ray
-        // ~ King move to non-attacked square
-        if let Some(checker_ray) = self.state.attacks.checkers.sliders.get(0) {
-            restricted = *checker_ray;
+        let _ = self.for_each_move_impl::<true, _>(|p_move| {
+            moves.push(p_move);
+            ControlFlow::Continue(())
+        });
+    }
+
+    fn for_each_move_impl<const EVASIONS: bool, F>(&self, mut f: F) -> ControlFlow<()>
+    where
+        F: FnMut(MoveExt) -> ControlFlow<()>,
+    {
+        // initial king move generation
+        // positions without a king are not legal, but shouldn't take the generator down
+        if let Some(king_sq) = self.state.board_ext.king_bb.to_square() {
+            let king_moves = self.pseudo_king_moves(king_sq) & !self.state.attacks.bb;
+
+            for to_square in king_moves.set_iter() {
+                let p_move = MoveExt {
+                    from: king_sq,
+                    to: to_square,
+                    piece_kind: King,
+                    promotion: None,
+                    capture: self.state.resolve_capture_only(to_square),
+                };
+
+                f(p_move)?;
+            }
         }
 
-        // Generate other piece moves
-        let friendly = self
-            .state
-            .position
-            .board
-            .get_color_bb(self.state.position.active_color);
+        let (restricted, pawn_restricted) = match self.check_restrictions() {
+            Some(restrictions) => restrictions,
+            // double check - forced king move
+            None => return ControlFlow::Continue(()),
+        };
 
-        for square in friendly.set_iter() {
-            let square_bb = BitBoard::from_square(square);
+        let pieces = if EVASIONS {
+            self.evasion_candidates(restricted, pawn_restricted)
+        } else {
+            self.state
+                .position
+                .board
+                .get_color_bb(self.state.position.active_color)
+        };
 
-            // Handle pins
-            let mut restricted = restricted;
+        // Generate other piece moves
+        for square in pieces.set_iter() {
+            let p_kind = self.state.board_ext.get(square).unwrap().kind;
 
-            for pinned in &self.state.attacks.pinners {
-                if square_bb & *pinned != 0 {
-                    restricted &= *pinned;
-                    break;
-                }
-            }
+            let restricted = if p_kind == Pawn {
+                pawn_restricted
+            } else {
+                restricted
+            };
+            let restricted = self.pin_restriction(square, restricted);
 
             // Generate moves
-            let p_kind = self.state.board_ext.get(square).unwrap().kind;
             let p_moves = self.pseudo_moves(square, p_kind);
 
             if p_kind == Pawn {
@@ -136,6 +319,10 @@ impl<'a> Generator<'a> {
                 };
 
                 if promotable {
+                    if !self.flags.promotions {
+                        continue;
+                    }
+
                     for to_square in legal_moves.set_iter() {
                         let p_move = MoveExt {
                             from: square,
@@ -146,10 +333,10 @@ impl<'a> Generator<'a> {
                         };
 
                         for promote_to in PieceKind::PROMOTABLE {
-                            moves.push(MoveExt {
+                            f(MoveExt {
                                 promotion: Some(promote_to),
                                 ..p_move
-                            });
+                            })?;
                         }
                     }
                 } else {
@@ -162,7 +349,7 @@ impl<'a> Generator<'a> {
                             capture: self.state.resolve_capture(to_square),
                         };
 
-                        moves.push(p_move);
+                        f(p_move)?;
                     }
                 }
             } else if p_kind != King {
@@ -178,16 +365,153 @@ impl<'a> Generator<'a> {
                         capture: self.state.resolve_capture_only(to_square),
                     };
 
-                    moves.push(p_move);
+                    f(p_move)?;
                 }
             }
         }
 
         // Castling moves
-        let castling = self.castling_moves();
-        moves.extend(castling);
+        if !EVASIONS {
+            for p_move in self.castling_moves() {
+                f(p_move)?;
+            }
+        }
 
-        moves
+        ControlFlow::Continue(())
+    }
+
+    /// Pieces other than the king that may be able to move to the `restricted` squares.
+    ///
+    /// This is a superset of the pieces with legal moves out of check, which still have to be
+    /// generated and restricted as usual.
+    fn evasion_candidates(&self, restricted: BitBoard, pawn_restricted: BitBoard) -> BitBoard {
+        let board = &self.state.position.board;
+        let active_color = self.state.position.active_color;
+        let pawns = board.get_piece_kind_bb(Pawn);
+
+        // captures and blocks
+        let mut candidates = BitBoard::ZERO;
+        for square in restricted.set_iter() {
+            candidates |= attackers_to(board, square, board.get_full_bb());
+        }
+
+        // capturing a checking pawn en passant
+        for square in (pawn_restricted & !restricted).set_iter() {
+            candidates |= Self::pawn_attacks(square, !active_color) & pawns;
+        }
+
+        // blocking with single and double pawn pushes
+        candidates |= match active_color {
+            Color::White => (restricted >> 8) | (restricted >> 16),
+            Color::Black => (restricted << 8) | (restricted << 16),
+        } & pawns;
+
+        candidates & board.get_color_bb(active_color) & !self.state.board_ext.king_bb
+    }
+
+    /// Squares that non-king pieces are restricted to moving to, to resolve any checks.
+    ///
+    /// Returns the restrictions for regular pieces and pawns respectively, which differ only if
+    /// a checking pawn can be captured en passant. Returns `None` in double check, where only
+    /// the king can move.
+    fn check_restrictions(&self) -> Option<(BitBoard, BitBoard)> {
+        // Double check
+        // - Forced king move
+        if self.state.attacks.checkers.len() > 1 {
+            return None;
+        }
+
+        let mut restricted = BitBoard(u64::MAX);
+        let mut pawn_restricted = restricted;
+
+        // Melee check
+        // - Checker can be captured
+        // - Checking pawn can be captured en passant
+        // ~ King move to non-attacked square
+        if let Some(checker_sq) = self.state.attacks.checkers.melee.first() {
+            restricted = BitBoard::from_square(*checker_sq);
+            pawn_restricted = restricted;
+
+            if let Some(ep_target) = self.state.position.ep_target {
+                if self.state.ep_captured_square(ep_target) == *checker_sq {
+                    pawn_restricted |= BitBoard::from_square(ep_target);
+                }
+            }
+        }
+
+        // Sliding check
+        // - Checker can be captured
+        // - Checker can be blocked along attack-ray
+        // ~ King move to non-attacked square
+        if let Some(checker_ray) = self.state.attacks.checkers.sliders.first() {
+            restricted = *checker_ray;
+            pawn_restricted = restricted;
+        }
+
+        Some((restricted, pawn_restricted))
+    }
+
+    /// Further restrict the movement of the piece on `square` along its pinning ray, if any.
+    #[inline]
+    fn pin_restriction(&self, square: Square, restricted: BitBoard) -> BitBoard {
+        let square_bb = BitBoard::from_square(square);
+
+        for pinned in &self.state.attacks.pinners {
+            if square_bb & *pinned != 0 {
+                return restricted & *pinned;
+            }
+        }
+
+        restricted
+    }
+
+    /// Check if a single move is legal, without generating all moves.
+    pub fn is_legal(&self, p_move: Move) -> bool {
+        let active_color = self.state.position.active_color;
+
+        let kind = match self.state.board_ext.get(p_move.from) {
+            Some(piece) if piece.color == active_color => piece.kind,
+            _ => return false,
+        };
+
+        // promotion must be present exactly when a pawn reaches the last rank
+        let last_rank = match active_color {
+            Color::White => 7,
+            Color::Black => 0,
+        };
+        let promotable = kind == Pawn && p_move.to.rank() == last_rank;
+
+        match p_move.promotion {
+            Some(_) if !self.flags.promotions => return false,
+            Some(promotion) if !promotable || !PieceKind::PROMOTABLE.contains(&promotion) => {
+                return false
+            }
+            None if promotable => return false,
+            _ => {}
+        }
+
+        let to_bb = BitBoard::from_square(p_move.to);
+
+        if kind == King {
+            let king_moves = self.pseudo_king_moves(p_move.from) & !self.state.attacks.bb;
+
+            return king_moves & to_bb != 0
+                || self.castling_moves().iter().any(|m| m.to == p_move.to);
+        }
+
+        let (restricted, pawn_restricted) = match self.check_restrictions() {
+            Some(restrictions) => restrictions,
+            None => return false,
+        };
+
+        let restricted = if kind == Pawn {
+            pawn_restricted
+        } else {
+            restricted
+        };
+        let restricted = self.pin_restriction(p_move.from, restricted);
+
+        self.pseudo_moves(p_move.from, kind) & restricted & to_bb != 0
     }
 }
 
@@ -304,8 +628,15 @@ impl<'a> Generator<'a> {
         let blockers = self.state.position.board.get_full_bb();
 
         // fake a piece for ep
-        if let Some(ep_target) = self.state.position.ep_target {
-            unfriendly |= BitBoard::from_square(ep_target);
+        if let Some(ep_target) = self
+            .state
+            .position
+            .ep_target
+            .filter(|_| self.flags.en_passant)
+        {
+            if !self.is_ep_pinned(square, ep_target) {
+                unfriendly |= BitBoard::from_square(ep_target);
+            }
         }
 
         let start = BitBoard::from_square(square);
@@ -335,7 +666,7 @@ impl<'a> Generator<'a> {
 
                 if blockers & next == 0 {
                     // single push
-                    if square.rank() > 1 {
+                    if square.rank() > 0 {
                         moves |= next;
 
                         // double push
@@ -352,6 +683,28 @@ impl<'a> Generator<'a> {
         moves
     }
 
+    /// Check if capturing en passant from `square` would leave the king in check.
+    ///
+    /// Both the capturing and the captured pawn leave their squares, so this isn't caught by
+    /// the regular pin detection, which only considers a single friendly piece between the king
+    /// and an attacker. This happens along the rank, when both pawns stand between the king and
+    /// an attacker, or along a diagonal, when the captured pawn is the only blocker.
+    fn is_ep_pinned(&self, square: Square, ep_target: Square) -> bool {
+        let king_sq = match self.state.board_ext.king_bb.to_square() {
+            Some(king_sq) => king_sq,
+            None => return false,
+        };
+
+        let board = &self.state.position.board;
+        let unfriendly = board.get_color_bb(self.state.position.active_color.opposite());
+
+        let vacated = BitBoard::from_square(square)
+            | BitBoard::from_square(self.state.ep_captured_square(ep_target));
+        let occupancy = (board.get_full_bb() & !vacated) | BitBoard::from_square(ep_target);
+
+        attackers_to(board, king_sq, occupancy) & unfriendly != 0
+    }
+
     pub fn king_attacks(square: Square) -> BitBoard {
         tables::KING_ATTACKS[square.raw_index() as usize]
     }
@@ -386,12 +739,12 @@ impl<'a> Generator<'a> {
         let mut checks_boo = CastlingChecks::zero();
         checks_boo.clear = BitBoard(start << 4 & !(1 << 63));
         checks_boo.safe = BitBoard(start << 3);
-        checks_boo.to_sq = BitBoard(1 << 58).to_square_unchecked();
+        checks_boo.to_sq = BitBoard(1 << 62).to_square_unchecked();
 
         let mut checks_booo = CastlingChecks::zero();
         checks_booo.clear = BitBoard(start);
         checks_booo.safe = BitBoard(start << 1);
-        checks_booo.to_sq = BitBoard(1 << 62).to_square_unchecked();
+        checks_booo.to_sq = BitBoard(1 << 58).to_square_unchecked();
 
         [checks_woo, checks_wooo, checks_boo, checks_booo]
     };
@@ -399,13 +752,21 @@ impl<'a> Generator<'a> {
     fn castling_moves(&self) -> SmallVec<[MoveExt; 2]> {
         let mut moves = SmallVec::new();
 
+        if !self.flags.castling {
+            return moves;
+        }
+
+        let king_sq = match self.state.board_ext.king_bb.to_square() {
+            Some(king_sq) => king_sq,
+            None => return moves,
+        };
         let blockers = self.state.position.board.get_full_bb();
 
         let mut do_checks = |checks: CastlingChecks| {
             if checks.clear & blockers == 0 && checks.safe & self.state.attacks.bb == 0 {
                 moves.push(MoveExt {
                     piece_kind: King,
-                    from: self.state.board_ext.king_bb.to_square_unchecked(),
+                    from: king_sq,
                     to: checks.to_sq,
                     promotion: None,
                     capture: None,
@@ -413,43 +774,19 @@ impl<'a> Generator<'a> {
             }
         };
 
-        match self.state.position.active_color {
-            Color::White => {
-                if self
-                    .state
-                    .position
-                    .castling
-                    .contains(CastlingRights::WHITE_OO)
-                {
-                    (do_checks)(Self::CASTLING_CHECKS[0]);
-                }
-                if self
-                    .state
-                    .position
-                    .castling
-                    .contains(CastlingRights::WHITE_OOO)
-                {
-                    (do_checks)(Self::CASTLING_CHECKS[1]);
-                }
-            }
-            Color::Black => {
-                if self
-                    .state
-                    .position
-                    .castling
-                    .contains(CastlingRights::BLACK_OO)
-                {
-                    (do_checks)(Self::CASTLING_CHECKS[2]);
-                }
-                if self
-                    .state
-                    .position
-                    .castling
-                    .contains(CastlingRights::BLACK_OOO)
-                {
-                    (do_checks)(Self::CASTLING_CHECKS[3]);
-                }
-            }
+        let castling = self.state.position.castling;
+        let active_color = self.state.position.active_color;
+
+        let (oo_checks, ooo_checks) = match active_color {
+            Color::White => (Self::CASTLING_CHECKS[0], Self::CASTLING_CHECKS[1]),
+            Color::Black => (Self::CASTLING_CHECKS[2], Self::CASTLING_CHECKS[3]),
+        };
+
+        if castling.has_kingside(active_color) {
+            (do_checks)(oo_checks);
+        }
+        if castling.has_queenside(active_color) {
+            (do_checks)(ooo_checks);
         }
 
         moves
@@ -461,7 +798,8 @@ impl<'a> Generator<'a> {
 struct CastlingChecks {
     /// Squares in between the king and rook are not occupied.
     clear: BitBoard,
-    /// Castling squares are not under attack.
+    /// Castling squares are not under attack, including the king's own square since castling
+    /// out of check isn't allowed.
     safe: BitBoard,
     /// Final square.
     to_sq: Square,
@@ -480,8 +818,10 @@ impl CastlingChecks {
 
 #[cfg(test)]
 mod test {
+    use std::str::FromStr;
+
     use super::*;
-    use sealion_board::Position;
+    use sealion_board::{Capture, CastlingRights, IntoEnumIterator, Piece, Position};
 
     struct MoveTester {
         pub name: &'static str,
@@ -496,10 +836,10 @@ mod test {
             F: Fn(Generator<'_>, Square) -> BitBoard,
         {
             let position = sealion_fen::from_str(self.fen)
-                .expect(&format!("`{}` failed due to bad fen", self.name));
+                .unwrap_or_else(|_| panic!("`{}` failed due to bad fen", self.name));
             let state = PositionState::generate(&position);
             let square = Square::try_from(self.sq)
-                .expect(&format!("`{}` failed due to bad square", self.name));
+                .unwrap_or_else(|_| panic!("`{}` failed due to bad square", self.name));
             let generator = Generator::new(&state);
 
             let result = f(generator, square);
@@ -584,7 +924,7 @@ mod test {
                 name: "king side",
                 sq: (4, 0),
                 fen: "rnbqkbnr/pppp1ppp/8/K3p3/1P6/8/PP1PPPPP/RNBQ1BNR w kq - 0 1",
-                result: 0x03_82_01_80_00_00,
+                result: 0x03_02_01_00_00_00,
             },
         ];
 
@@ -593,6 +933,520 @@ mod test {
         }
     }
 
+    #[test]
+    fn ep_discovered_check_along_rank() {
+        let position = sealion_fen::from_str("8/8/8/K2Pp2r/8/8/8/7k w - e6 0 1").unwrap();
+        let state = PositionState::generate(&position);
+        let generator = Generator::new(&state);
+
+        let pawn_sq = Square::from_str("d5").unwrap();
+        let ep_sq = Square::from_str("e6").unwrap();
+
+        assert!(!generator.pseudo_pawn_moves(pawn_sq).get(ep_sq));
+
+        match MoveList::generate(&state) {
+            MoveList::Moves(moves) => {
+                assert!(!moves.iter().any(|m| m.from == pawn_sq && m.to == ep_sq));
+                assert!(moves.iter().any(|m| m.from == pawn_sq));
+            }
+            _ => panic!("position is not mate"),
+        }
+    }
+
+    #[test]
+    fn ep_capture_checking_pawn() {
+        let position = sealion_fen::from_str("8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1").unwrap();
+        let state = PositionState::generate(&position);
+
+        let pawn_sq = Square::from_str("e4").unwrap();
+        let ep_sq = Square::from_str("d3").unwrap();
+
+        match MoveList::generate(&state) {
+            MoveList::Moves(moves) => {
+                assert!(moves.iter().any(|m| m.from == pawn_sq && m.to == ep_sq));
+                // pushing the pawn does not resolve the check
+                assert!(!moves.iter().any(|m| m.from == pawn_sq && m.to != ep_sq));
+            }
+            _ => panic!("position is not mate"),
+        }
+    }
+
+    #[test]
+    fn promotion_captures() {
+        let position = sealion_fen::from_str("3r3k/4P3/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let state = PositionState::generate(&position);
+        let d8 = Square::from_str("d8").unwrap();
+
+        let moves = match Generator::new(&state).generate() {
+            MoveList::Moves(moves) => moves,
+            _ => panic!("position is not mate"),
+        };
+        let captures: Vec<_> = moves.iter().filter(|m| m.to == d8).collect();
+
+        assert_eq!(captures.len(), 4);
+        for promotion in PieceKind::PROMOTABLE {
+            let p_move = captures
+                .iter()
+                .find(|m| m.promotion == Some(promotion))
+                .expect("missing promotion capture");
+            assert!(matches!(p_move.capture, Some(Capture::Regular(Rook))));
+
+            let mut new_position = position.clone();
+            new_position.apply_move_unchecked(**p_move);
+            assert_eq!(
+                new_position.board.get(d8),
+                Some(Piece {
+                    color: Color::White,
+                    kind: promotion,
+                })
+            );
+            // no other piece kinds are left behind on the square
+            for kind in PieceKind::iter().filter(|kind| *kind != promotion) {
+                assert!(!new_position.board.get_piece_kind_bb(kind).get(d8));
+            }
+        }
+    }
+
+    #[test]
+    fn attackers_to() {
+        let position = sealion_fen::from_str("3r4/2n5/2p1k3/8/2P2N2/8/B7/K2R3Q w - - 0 1").unwrap();
+        let board = &position.board;
+        let squares = |s: &[&str]| {
+            s.iter().fold(BitBoard::ZERO, |bb, s| {
+                bb | BitBoard::from_square(Square::from_str(s).unwrap())
+            })
+        };
+        let d5 = Square::from_str("d5").unwrap();
+
+        // the bishop on a2 is blocked by the pawn
+        assert_eq!(
+            super::attackers_to(board, d5, board.get_full_bb()),
+            squares(&["c4", "f4", "d1", "h1", "c7", "d8", "e6", "c6"])
+        );
+
+        // removing the pawn reveals the bishop
+        let occupancy = board.get_full_bb() & !squares(&["c4"]);
+        assert_eq!(
+            super::attackers_to(board, d5, occupancy),
+            squares(&["a2", "f4", "d1", "h1", "c7", "d8", "e6", "c6"])
+        );
+    }
+
+    #[test]
+    fn display() {
+        let position = Position::starting();
+        let state = PositionState::generate(&position);
+
+        assert_eq!(
+            MoveList::generate(&state).to_string(),
+            "20 legal moves: a2a3 a2a4 b1a3 b1c3 b2b3 b2b4 c2c3 c2c4 d2d3 d2d4 \
+             e2e3 e2e4 f2f3 f2f4 g1f3 g1h3 g2g3 g2g4 h2h3 h2h4"
+        );
+        assert_eq!(MoveList::Checkmate.to_string(), "Checkmate");
+        assert_eq!(MoveList::Stalemate.to_string(), "Stalemate");
+    }
+
+    #[test]
+    fn contains() {
+        let position = Position::starting();
+        let moves = MoveList::generate(&PositionState::generate(&position));
+
+        assert!(moves.contains(&Move::from_str("e2e4").unwrap()));
+        assert!(moves.contains(&Move::from_str("g1f3").unwrap()));
+        assert!(!moves.contains(&Move::from_str("e2e5").unwrap()));
+        assert!(!moves.contains(&Move::from_str("e7e5").unwrap()));
+        // a pawn can't promote from the second rank
+        assert!(!moves.contains(&Move::from_str("e2e4q").unwrap()));
+
+        assert!(!MoveList::Checkmate.contains(&Move::from_str("e2e4").unwrap()));
+        assert!(!MoveList::Stalemate.contains(&Move::from_str("e2e4").unwrap()));
+    }
+
+    #[test]
+    fn mate_classification() {
+        let classify = |fen| {
+            let position = sealion_fen::from_str(fen).unwrap();
+            MoveList::generate(&PositionState::generate(&position))
+        };
+
+        // back rank mate
+        assert!(matches!(
+            classify("6k1/5ppp/8/8/8/8/8/3R2K1 b - - 0 1"),
+            MoveList::Moves(_)
+        ));
+        assert!(matches!(
+            classify("3R2k1/5ppp/8/8/8/8/8/6K1 b - - 0 1"),
+            MoveList::Checkmate
+        ));
+        // smothered mate
+        assert!(matches!(
+            classify("6rk/5Npp/8/8/8/8/8/6K1 b - - 0 1"),
+            MoveList::Checkmate
+        ));
+        // scholar's mate
+        assert!(matches!(
+            classify("r1bqkb1r/pppp1Qpp/2n2n2/4p3/2B1P3/8/PPPP1PPP/RNB1K1NR b KQkq - 0 4"),
+            MoveList::Checkmate
+        ));
+        assert!(matches!(
+            classify("k7/8/1Q6/8/8/8/8/7K b - - 0 1"),
+            MoveList::Stalemate
+        ));
+        // no moves for the king, but other pieces can move
+        assert!(matches!(
+            classify("k7/p7/1Q6/8/8/8/8/7K b - - 0 1"),
+            MoveList::Moves(_)
+        ));
+    }
+
+    #[test]
+    fn black_promotion_push() {
+        let position = sealion_fen::from_str("4k3/8/8/8/8/8/3p4/K7 b - - 0 1").unwrap();
+        let state = PositionState::generate(&position);
+        let d1 = Square::from_str("d1").unwrap();
+
+        let moves = match Generator::new(&state).generate() {
+            MoveList::Moves(moves) => moves,
+            _ => panic!("position is not mate"),
+        };
+        let pushes: Vec<_> = moves.iter().filter(|m| m.to == d1).collect();
+
+        assert_eq!(pushes.len(), 4);
+        assert!(pushes.iter().all(|m| m.promotion.is_some()));
+    }
+
+    #[test]
+    fn for_each_move() {
+        let position = sealion_fen::from_str(
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        )
+        .unwrap();
+        let state = PositionState::generate(&position);
+        let generator = Generator::new(&state);
+
+        let moves = match generator.generate() {
+            MoveList::Moves(moves) => moves,
+            _ => panic!("position is not mate"),
+        };
+
+        // count all moves
+        let mut count = 0;
+        let flow = generator.for_each_move(|_| {
+            count += 1;
+            ControlFlow::Continue(())
+        });
+
+        assert_eq!(flow, ControlFlow::Continue(()));
+        assert_eq!(count, moves.len());
+
+        // short-circuit after the first move
+        let mut count = 0;
+        let flow = generator.for_each_move(|_| {
+            count += 1;
+            ControlFlow::Break(())
+        });
+
+        assert_eq!(flow, ControlFlow::Break(()));
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn generate_into() {
+        let mut buffer = Vec::new();
+
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        ] {
+            let position = sealion_fen::from_str(fen).unwrap();
+            let state = PositionState::generate(&position);
+            let generator = Generator::new(&state);
+
+            generator.generate_into(&mut buffer);
+
+            match generator.generate() {
+                MoveList::Moves(moves) => {
+                    assert_eq!(buffer.len(), moves.len());
+                    for (a, b) in buffer.iter().zip(moves.iter()) {
+                        assert_eq!(a.to_move().to_string(), b.to_move().to_string());
+                    }
+                }
+                _ => panic!("position is not mate"),
+            }
+        }
+    }
+
+    #[test]
+    fn try_new() {
+        let position = sealion_fen::from_str("8/8/8/8/8/8/4P3/8 w - - 0 1").unwrap();
+        let state = PositionState::generate(&position);
+        assert!(Generator::try_new(&state).is_none());
+
+        // only the side to move needs a king
+        let position = sealion_fen::from_str("8/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+        let state = PositionState::generate(&position);
+        assert!(Generator::try_new(&state).is_some());
+    }
+
+    #[test]
+    fn reuse_state() {
+        let position = sealion_fen::from_str("4k3/8/8/8/8/8/8/r3K3 w - - 0 1").unwrap();
+        let (moves, state) = MoveList::generate_with_state(&position);
+
+        let moves = match moves {
+            MoveList::Moves(moves) => moves,
+            _ => panic!("position is not mate"),
+        };
+        assert!(moves.iter().all(|m| m.to.rank() == 1));
+
+        // the whole first rank, through the king
+        for file in 1..8 {
+            assert!(state.attacks.bb.get(Square::at(0, file).unwrap()));
+        }
+        assert_eq!(state.attacks.checkers.sliders.len(), 1);
+    }
+
+    #[test]
+    fn castling() {
+        let castles = |fen: &str| -> Vec<String> {
+            let position = sealion_fen::from_str(fen).unwrap();
+            let state = PositionState::generate(&position);
+
+            match Generator::new(&state).generate() {
+                MoveList::Moves(moves) => moves
+                    .iter()
+                    .filter(|m| m.is_castle())
+                    .map(|m| m.to_move().to_string())
+                    .collect(),
+                _ => panic!("position is not mate"),
+            }
+        };
+
+        assert_eq!(
+            castles("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1"),
+            ["e1g1", "e1c1"]
+        );
+        assert_eq!(
+            castles("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1"),
+            ["e8g8", "e8c8"]
+        );
+
+        // king in check, all other castling squares are clear and safe
+        assert!(castles("4k3/8/8/8/8/8/4r3/R3K2R w KQ - 0 1").is_empty());
+        assert!(castles("r3k2r/4R3/8/8/8/8/8/4K3 b kq - 0 1").is_empty());
+    }
+
+    #[test]
+    fn gen_flags() {
+        let lans = |fen: &str, flags: GenFlags| -> Vec<String> {
+            let position = sealion_fen::from_str(fen).unwrap();
+            let state = PositionState::generate(&position);
+
+            match Generator::new(&state).with_flags(flags).generate() {
+                MoveList::Moves(moves) => moves.iter().map(|m| m.to_move().to_string()).collect(),
+                _ => panic!("position is not mate"),
+            }
+        };
+
+        let castling = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+        let all = lans(castling, GenFlags::ALL);
+        let no_castling = lans(
+            castling,
+            GenFlags {
+                castling: false,
+                ..GenFlags::ALL
+            },
+        );
+        let expected: Vec<_> = all
+            .iter()
+            .filter(|lan| *lan != "e1g1" && *lan != "e1c1")
+            .cloned()
+            .collect();
+        assert_eq!(all.len(), no_castling.len() + 2);
+        assert_eq!(no_castling, expected);
+
+        let ep = "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1";
+        assert!(lans(ep, GenFlags::ALL).contains(&"e5d6".to_string()));
+        let no_ep = lans(
+            ep,
+            GenFlags {
+                en_passant: false,
+                ..GenFlags::ALL
+            },
+        );
+        assert!(!no_ep.contains(&"e5d6".to_string()));
+        assert_eq!(no_ep.len(), lans(ep, GenFlags::ALL).len() - 1);
+
+        let promotion = "4k3/1P6/8/8/8/8/8/4K3 w - - 0 1";
+        let no_promotions = lans(
+            promotion,
+            GenFlags {
+                promotions: false,
+                ..GenFlags::ALL
+            },
+        );
+        assert_eq!(
+            no_promotions.len(),
+            lans(promotion, GenFlags::ALL).len() - 4
+        );
+        assert!(no_promotions.iter().all(|lan| !lan.starts_with("b7")));
+
+        // nothing left to generate, but that doesn't make it stalemate
+        let position = sealion_fen::from_str("8/8/8/8/8/1k6/1p6/1K6 w - - 0 1").unwrap();
+        let state = PositionState::generate(&position);
+        let flags = GenFlags {
+            castling: false,
+            ..GenFlags::ALL
+        };
+        assert!(matches!(
+            Generator::new(&state).generate(),
+            MoveList::Stalemate
+        ));
+        assert!(matches!(
+            Generator::new(&state).with_flags(flags).generate(),
+            MoveList::Moves(moves) if moves.is_empty()
+        ));
+    }
+
+    #[test]
+    fn generate_evasions() {
+        fn walk(position: &Position, depth: usize, checks: &mut usize) {
+            let state = PositionState::generate(position);
+            let generator = Generator::new(&state);
+
+            let mut moves = Vec::new();
+            let mut evasions = Vec::new();
+            generator.generate_into(&mut moves);
+            generator.generate_evasions(&mut evasions);
+
+            if state.attacks.checkers.is_empty() {
+                assert!(evasions.is_empty());
+            } else {
+                *checks += 1;
+                assert_eq!(evasions, moves, "{position:?}");
+            }
+
+            if depth > 1 {
+                for p_move in moves {
+                    let mut position = position.clone();
+                    position.apply_move_unchecked(p_move);
+                    walk(&position, depth - 1, checks);
+                }
+            }
+        }
+
+        let fens = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            // ep capture of the checking pawn
+            "8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1",
+        ];
+
+        let mut checks = 0;
+        for fen in fens {
+            walk(&sealion_fen::from_str(fen).unwrap(), 4, &mut checks);
+        }
+        assert!(checks > 100);
+    }
+
+    #[test]
+    fn castling_rook_capture() {
+        let mut position = sealion_fen::from_str("r3k3/8/8/8/8/8/8/R3K2R w KQq - 0 1").unwrap();
+        let state = PositionState::generate(&position);
+
+        let p_move = MoveExt {
+            piece_kind: Rook,
+            from: Square::from_str("a1").unwrap(),
+            to: Square::from_str("a8").unwrap(),
+            promotion: None,
+            capture: state.resolve_capture(Square::from_str("a8").unwrap()),
+        };
+        position.apply_move_unchecked(p_move);
+
+        // both rooks are gone from their corners
+        assert_eq!(position.castling, CastlingRights::WHITE_OO);
+    }
+
+    #[test]
+    fn kingless() {
+        // castling rights without a king shouldn't generate castling moves either
+        let position = sealion_fen::from_str("8/8/8/8/8/8/4P3/8 w KQ - 0 1").unwrap();
+        let state = PositionState::generate(&position);
+        let generator = Generator::new(&state);
+
+        match generator.generate() {
+            MoveList::Moves(moves) => assert_eq!(moves.len(), 2),
+            _ => panic!("position is not mate"),
+        }
+    }
+
+    #[test]
+    fn is_legal() {
+        for fen in [
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/8/8/K2Pp2r/8/8/8/7k w - e6 0 1",
+            "8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1",
+            "r1bqkbnr/pppp1Qpp/2n5/4p3/2B1P3/8/PPPP1PPP/RNB1K1NR b KQkq - 0 4",
+        ] {
+            let position = sealion_fen::from_str(fen).unwrap();
+            let state = PositionState::generate(&position);
+            let generator = Generator::new(&state);
+
+            let moves = match generator.generate() {
+                MoveList::Moves(moves) => moves,
+                _ => vec![],
+            };
+
+            let promotions = [None].into_iter().chain(PieceKind::iter().map(Some));
+
+            for from in 0..64 {
+                for to in 0..64 {
+                    for promotion in promotions.clone() {
+                        let p_move = Move {
+                            from: Square::from_index_unchecked(from),
+                            to: Square::from_index_unchecked(to),
+                            promotion,
+                        };
+
+                        let expected = moves.iter().any(|m| m.to_move() == p_move);
+                        assert_eq!(generator.is_legal(p_move), expected, "{fen}: {p_move}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn attacks_by() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            // check along a rank
+            "4k3/8/8/8/r3K3/8/8/8 w - - 0 1",
+        ] {
+            let position = sealion_fen::from_str(fen).unwrap();
+            let state = PositionState::generate(&position);
+            let color = position.active_color.opposite();
+
+            // state attacks pass through the king of the side to move
+            let mut board = position.board.clone();
+            board.set(state.board_ext.king_bb.to_square().unwrap(), None);
+
+            assert_eq!(super::attacks_by(&board, color), state.attacks.bb, "{fen}");
+        }
+
+        // sliders stop at the king otherwise
+        let position = sealion_fen::from_str("4k3/8/8/8/r3K3/8/8/8 w - - 0 1").unwrap();
+        let attacks = super::attacks_by(&position.board, Color::Black);
+        assert!(attacks.get(Square::from_str("e4").unwrap()));
+        assert!(!attacks.get(Square::from_str("f4").unwrap()));
+    }
+
     #[test]
     fn full_move_gen() {
         let position = Position::starting();
@@ -102,39 +102,35 @@ pub const KING_ATTACKS: [BitBoard; 64] = {
         let square = Square::from_index_unchecked(i);
         let start = 1 << i;
 
-        // E
+        // W
         if square.file() > 0 {
-            moves |= start >> 9;
             moves |= start >> 1;
-            moves |= start << 7;
+
+            if square.rank() > 0 {
+                moves |= start >> 9;
+            }
+            if square.rank() < 7 {
+                moves |= start << 7;
+            }
         }
-        // W
+        // E
         if square.file() < 7 {
-            moves |= start >> 7;
             moves |= start << 1;
-            moves |= start << 9;
+
+            if square.rank() > 0 {
+                moves |= start >> 7;
+            }
+            if square.rank() < 7 {
+                moves |= start << 9;
+            }
         }
         // N
-        let mut mask = 0;
-        mask |= start << 7;
-        mask |= start << 8;
-        mask |= start << 9;
-
-        if square.rank() == 7 {
-            moves &= !mask;
-        } else {
-            moves |= mask;
+        if square.rank() < 7 {
+            moves |= start << 8;
         }
         // S
-        let mut mask = 0;
-        mask |= start >> 7;
-        mask |= start >> 8;
-        mask |= start >> 9;
-
-        if square.rank() == 0 {
-            moves &= !mask;
-        } else {
-            moves |= mask;
+        if square.rank() > 0 {
+            moves |= start >> 8;
         }
 
         all_moves[i as usize] = BitBoard(moves);
@@ -143,3 +139,65 @@ pub const KING_ATTACKS: [BitBoard; 64] = {
 
     all_moves
 };
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Compute attacks from `square` by applying each (rank, file) offset with bounds checks.
+    fn offset_attacks(square: Square, offsets: &[(i8, i8)]) -> BitBoard {
+        let mut attacks = BitBoard::ZERO;
+
+        for (d_rank, d_file) in offsets {
+            let rank = square.rank() as i8 + d_rank;
+            let file = square.file() as i8 + d_file;
+
+            if (0..8).contains(&rank) && (0..8).contains(&file) {
+                attacks.set(Square::at(rank as u8, file as u8).unwrap(), true);
+            }
+        }
+
+        attacks
+    }
+
+    fn assert_table(name: &str, table: &[BitBoard], offset: usize, offsets: &[(i8, i8)]) {
+        for i in 0..64 {
+            let square = Square::from_index_unchecked(i);
+
+            assert_eq!(
+                table[i as usize + offset],
+                offset_attacks(square, offsets),
+                "{name} attacks differ on {square}"
+            );
+        }
+    }
+
+    #[test]
+    fn pawn_attacks() {
+        assert_table("white pawn", &PAWN_ATTACKS, 0, &[(1, -1), (1, 1)]);
+        assert_table("black pawn", &PAWN_ATTACKS, 64, &[(-1, -1), (-1, 1)]);
+    }
+
+    #[test]
+    fn knight_attacks() {
+        #[rustfmt::skip]
+        let offsets = [
+            (2, -1), (2, 1), (1, -2), (1, 2),
+            (-1, -2), (-1, 2), (-2, -1), (-2, 1),
+        ];
+
+        assert_table("knight", &KNIGHT_ATTACKS, 0, &offsets);
+    }
+
+    #[test]
+    fn king_attacks() {
+        #[rustfmt::skip]
+        let offsets = [
+            (1, -1), (1, 0), (1, 1),
+            (0, -1), (0, 1),
+            (-1, -1), (-1, 0), (-1, 1),
+        ];
+
+        assert_table("king", &KING_ATTACKS, 0, &offsets);
+    }
+}
@@ -0,0 +1,423 @@
+//! Move ordering, so that likely good moves are searched first.
+//!
+//! <https://www.chessprogramming.org/Move_Ordering>
+
+use sealion_board::{BitBoard, EnumCount, IntoEnumIterator, Move, MoveExt, PieceKind, Position};
+
+use crate::movegen::attackers_to;
+
+/// Quiet moves that caused a beta cutoff, stored per ply.
+///
+/// <https://www.chessprogramming.org/Killer_Heuristic>
+#[derive(Debug, Clone, Default)]
+pub struct KillerTable {
+    moves: Vec<[Option<Move>; 2]>,
+}
+
+impl KillerTable {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The killer moves at `ply`, most recent first.
+    #[inline]
+    pub fn get(&self, ply: usize) -> [Option<Move>; 2] {
+        self.moves.get(ply).copied().unwrap_or_default()
+    }
+
+    /// Check if `p_move` is a killer move at `ply`.
+    #[inline]
+    pub fn contains(&self, ply: usize, p_move: Move) -> bool {
+        self.get(ply).contains(&Some(p_move))
+    }
+
+    /// Store a killer move at `ply`, evicting the oldest one.
+    pub fn store(&mut self, ply: usize, p_move: Move) {
+        if ply >= self.moves.len() {
+            self.moves.resize(ply + 1, [None; 2]);
+        }
+
+        let killers = &mut self.moves[ply];
+        if killers[0] != Some(p_move) {
+            killers[1] = killers[0];
+            killers[0] = Some(p_move);
+        }
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.moves.clear();
+    }
+}
+
+/// Cutoff counts of quiet moves, indexed by the moving piece and its target square.
+///
+/// <https://www.chessprogramming.org/History_Heuristic>
+#[derive(Debug, Clone)]
+pub struct HistoryTable {
+    scores: [[u32; 64]; PieceKind::COUNT],
+}
+
+impl Default for HistoryTable {
+    fn default() -> Self {
+        Self {
+            scores: [[0; 64]; PieceKind::COUNT],
+        }
+    }
+}
+
+impl HistoryTable {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The accumulated score of `p_move`.
+    #[inline]
+    pub fn get(&self, p_move: &MoveExt) -> u32 {
+        self.scores[p_move.piece_kind as usize][p_move.to.raw_index() as usize]
+    }
+
+    /// Record a cutoff caused by `p_move` at the given remaining `depth`.
+    ///
+    /// Cutoffs closer to the root are weighted more heavily.
+    #[inline]
+    pub fn record(&mut self, p_move: &MoveExt, depth: usize) {
+        let depth = u32::try_from(depth).unwrap_or(u32::MAX);
+        let score = &mut self.scores[p_move.piece_kind as usize][p_move.to.raw_index() as usize];
+        *score = score.saturating_add(depth.saturating_mul(depth));
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Search heuristics used to order quiet moves.
+#[derive(Debug, Clone, Default)]
+pub struct Heuristics {
+    pub killers: KillerTable,
+    pub history: HistoryTable,
+}
+
+impl Heuristics {
+    const KILLER_BONUS: [u32; 2] = [1 << 31, 1 << 30];
+
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the tables after `p_move` caused a beta cutoff.
+    ///
+    /// Captures are already ordered well by MVV-LVA, so only quiet moves are recorded.
+    pub fn update_on_cutoff(&mut self, ply: usize, depth: usize, p_move: &MoveExt) {
+        if p_move.is_capture() {
+            return;
+        }
+
+        self.killers.store(ply, p_move.to_move());
+        self.history.record(p_move, depth);
+    }
+
+    /// Ordering bonus of a quiet move, higher is searched first.
+    pub fn bonus(&self, ply: usize, p_move: &MoveExt) -> u32 {
+        let killers = self.killers.get(ply);
+        let killer_bonus = killers
+            .iter()
+            .zip(Self::KILLER_BONUS)
+            .find(|(killer, _)| **killer == Some(p_move.to_move()))
+            .map_or(0, |(_, bonus)| bonus);
+
+        killer_bonus.saturating_add(self.history.get(p_move))
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.killers.clear();
+        self.history.clear();
+    }
+}
+
+/// Sort key of a move, lower keys are searched first.
+fn sort_key(p_move: &MoveExt, tt_move: Option<Move>, bonus: u32) -> (u8, i64) {
+    if tt_move == Some(p_move.to_move()) {
+        return (0, 0);
+    }
+
+    let victim = match p_move.capture {
+        Some(capture) => capture.kind(),
+        None => return (2, -i64::from(bonus)),
+    };
+
+    // Most Valuable Victim - Least Valuable Attacker
+    let victim = i64::from(victim.score());
+    let attacker = i64::from(p_move.piece_kind.score());
+    (1, attacker - victim * 100)
+}
+
+/// Order moves for search.
+///
+/// The transposition table move is placed first, followed by captures in MVV-LVA order, and
+/// then all quiet moves in their original order.
+pub fn order_moves(moves: &mut [MoveExt], tt_move: Option<Move>) {
+    moves.sort_by_key(|p_move| sort_key(p_move, tt_move, 0));
+}
+
+/// Order moves for search at `ply`, like [`order_moves`], additionally ordering quiet moves
+/// by their [`Heuristics::bonus`].
+pub fn order_moves_with(
+    moves: &mut [MoveExt],
+    tt_move: Option<Move>,
+    ply: usize,
+    heuristics: &Heuristics,
+) {
+    moves.sort_by_cached_key(|p_move| sort_key(p_move, tt_move, heuristics.bonus(ply, p_move)));
+}
+
+/// Check if the static exchange on the target square of `p_move` wins at least `threshold`.
+///
+/// Both sides keep recapturing with their least valuable attacker for as long as it pays off,
+/// including attackers revealed behind others. Pins are not considered, and castling is treated
+/// as an even exchange.
+///
+/// <https://www.chessprogramming.org/Static_Exchange_Evaluation>
+pub fn see_ge(position: &Position, p_move: &MoveExt, threshold: i16) -> bool {
+    if p_move.is_castle() {
+        return threshold <= 0;
+    }
+
+    let board = &position.board;
+    let promotion = p_move
+        .promotion
+        .map_or(0, |kind| kind.score() - PieceKind::Pawn.score());
+    let captured = p_move.capture.map_or(0, |capture| capture.kind().score());
+
+    // we lose if the first capture isn't enough
+    let mut swap = captured + promotion - threshold;
+    if swap < 0 {
+        return false;
+    }
+
+    // ... and win if losing the moved piece is still enough
+    swap = p_move.promotion.unwrap_or(p_move.piece_kind).score() - swap;
+    if swap <= 0 {
+        return true;
+    }
+
+    let mut occupancy = board.get_full_bb() ^ BitBoard::from_square(p_move.from);
+    if let Some(captured_sq) = p_move.captured_square() {
+        occupancy &= !BitBoard::from_square(captured_sq);
+    }
+    occupancy |= BitBoard::from_square(p_move.to);
+
+    let mut color = position.active_color;
+    let mut result = true;
+
+    loop {
+        color = !color;
+
+        // removed pieces are masked out, and sliders behind them revealed
+        let attackers = attackers_to(board, p_move.to, occupancy);
+        let own_attackers = attackers & board.get_color_bb(color);
+        if own_attackers.is_empty() {
+            break;
+        }
+
+        result = !result;
+
+        // piece kinds are in ascending order of value
+        let (kind, attacker) = PieceKind::iter()
+            .map(|kind| (kind, own_attackers & board.get_piece_kind_bb(kind)))
+            .find(|(_, bb)| !bb.is_empty())
+            .unwrap();
+
+        if kind == PieceKind::King {
+            // the king can't recapture into a defended square
+            if attackers & board.get_color_bb(!color) != 0 {
+                result = !result;
+            }
+            break;
+        }
+
+        swap = kind.score() - swap;
+        if swap < i16::from(result) {
+            break;
+        }
+
+        let lsb = attacker.0 & attacker.0.wrapping_neg();
+        occupancy ^= BitBoard(lsb);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use sealion_board::{Capture, Square};
+
+    use super::*;
+
+    fn p_move(from: &str, to: &str, piece_kind: PieceKind, capture: Option<Capture>) -> MoveExt {
+        MoveExt {
+            from: Square::from_str(from).unwrap(),
+            to: Square::from_str(to).unwrap(),
+            piece_kind,
+            promotion: None,
+            capture,
+        }
+    }
+
+    fn assert_order(moves: &[MoveExt], expected: &[MoveExt]) {
+        let moves: Vec<_> = moves.iter().map(MoveExt::to_move).collect();
+        let expected: Vec<_> = expected.iter().map(MoveExt::to_move).collect();
+        assert_eq!(moves, expected);
+    }
+
+    #[test]
+    fn mvv_lva() {
+        let quiet = p_move("g1", "f3", PieceKind::Knight, None);
+        let queen_takes_pawn = p_move(
+            "d1",
+            "d7",
+            PieceKind::Queen,
+            Some(Capture::Regular(PieceKind::Pawn)),
+        );
+        let pawn_takes_queen = p_move(
+            "e4",
+            "d5",
+            PieceKind::Pawn,
+            Some(Capture::Regular(PieceKind::Queen)),
+        );
+        let pawn_takes_knight = p_move(
+            "e4",
+            "f5",
+            PieceKind::Pawn,
+            Some(Capture::Regular(PieceKind::Knight)),
+        );
+
+        let mut moves = [quiet, queen_takes_pawn, pawn_takes_knight, pawn_takes_queen];
+        order_moves(&mut moves, None);
+        assert_order(
+            &moves,
+            &[pawn_takes_queen, pawn_takes_knight, queen_takes_pawn, quiet],
+        );
+
+        // the tt move always goes first, even if quiet
+        order_moves(&mut moves, Some(quiet.to_move()));
+        assert_order(
+            &moves,
+            &[quiet, pawn_takes_queen, pawn_takes_knight, queen_takes_pawn],
+        );
+    }
+
+    #[test]
+    fn killers() {
+        let mut killers = KillerTable::new();
+        let e4 = p_move("e2", "e4", PieceKind::Pawn, None).to_move();
+        let nf3 = p_move("g1", "f3", PieceKind::Knight, None).to_move();
+        let nc3 = p_move("b1", "c3", PieceKind::Knight, None).to_move();
+
+        assert_eq!(killers.get(3), [None, None]);
+
+        killers.store(3, e4);
+        assert!(killers.contains(3, e4));
+        assert!(!killers.contains(2, e4));
+
+        // storing the same move twice doesn't evict the other killer
+        killers.store(3, nf3);
+        killers.store(3, nf3);
+        assert_eq!(killers.get(3), [Some(nf3), Some(e4)]);
+
+        killers.store(3, nc3);
+        assert_eq!(killers.get(3), [Some(nc3), Some(nf3)]);
+    }
+
+    #[test]
+    fn history() {
+        let mut heuristics = Heuristics::new();
+        let quiet = p_move("g1", "f3", PieceKind::Knight, None);
+        let capture = p_move(
+            "e4",
+            "d5",
+            PieceKind::Pawn,
+            Some(Capture::Regular(PieceKind::Pawn)),
+        );
+
+        heuristics.update_on_cutoff(0, 2, &quiet);
+        let score = heuristics.history.get(&quiet);
+        assert!(score > 0);
+
+        heuristics.update_on_cutoff(0, 2, &quiet);
+        assert!(heuristics.history.get(&quiet) > score);
+
+        heuristics.update_on_cutoff(0, 2, &capture);
+        assert_eq!(heuristics.history.get(&capture), 0);
+        assert!(!heuristics.killers.contains(0, capture.to_move()));
+    }
+
+    #[test]
+    fn heuristic_order() {
+        let mut heuristics = Heuristics::new();
+        let e4 = p_move("e2", "e4", PieceKind::Pawn, None);
+        let nf3 = p_move("g1", "f3", PieceKind::Knight, None);
+        let nc3 = p_move("b1", "c3", PieceKind::Knight, None);
+        let capture = p_move(
+            "e4",
+            "d5",
+            PieceKind::Pawn,
+            Some(Capture::Regular(PieceKind::Pawn)),
+        );
+
+        heuristics.history.record(&nc3, 4);
+        heuristics.update_on_cutoff(1, 1, &nf3);
+
+        let mut moves = [e4, nc3, nf3, capture];
+        order_moves_with(&mut moves, None, 1, &heuristics);
+        assert_order(&moves, &[capture, nf3, nc3, e4]);
+
+        // killers only apply to their own ply
+        order_moves_with(&mut moves, None, 0, &heuristics);
+        assert_order(&moves, &[capture, nc3, nf3, e4]);
+    }
+
+    #[test]
+    fn see_ge() {
+        use crate::PositionExt;
+
+        let see_ge = |fen: &str, lan: &str, threshold: i16| {
+            let position = sealion_fen::from_str(fen).unwrap();
+            let p_move = position.clone().apply_uci_move(lan).unwrap();
+            super::see_ge(&position, &p_move, threshold)
+        };
+
+        // undefended knight
+        const WINNING: &str = "4k3/8/8/3n4/4P3/8/8/4K3 w - - 0 1";
+        assert!(see_ge(WINNING, "e4d5", 0));
+        assert!(see_ge(WINNING, "e4d5", 300));
+        assert!(!see_ge(WINNING, "e4d5", 301));
+
+        // pawn defended by a pawn
+        const LOSING: &str = "4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1";
+        assert!(!see_ge(LOSING, "d1d5", 0));
+        assert!(see_ge(LOSING, "d1d5", -800));
+        assert!(!see_ge(LOSING, "d1d5", -799));
+
+        // the second rook recaptures through the first
+        const XRAY: &str = "3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1";
+        assert!(see_ge(XRAY, "d2d5", 100));
+        assert!(!see_ge(XRAY, "d2d5", 101));
+
+        // the king can only recapture if the square isn't defended
+        assert!(!see_ge("8/8/3k4/4p3/8/5N2/8/4K3 w - - 0 1", "f3e5", 0));
+        assert!(see_ge("8/8/3k4/4p3/3P4/5N2/8/4K3 w - - 0 1", "f3e5", 100));
+
+        // quiet move to an attacked square
+        assert!(!see_ge("4k3/8/3p4/8/8/5N2/8/4K3 w - - 0 1", "f3e5", 0));
+        assert!(see_ge("4k3/8/3p4/8/8/5N2/8/4K3 w - - 0 1", "f3g5", 0));
+    }
+}
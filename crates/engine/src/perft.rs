@@ -0,0 +1,297 @@
+//! Move path enumeration, used to verify the move generator.
+//!
+//! <https://www.chessprogramming.org/Perft>
+
+use std::collections::{BTreeMap, HashMap};
+use std::ops::ControlFlow;
+
+use sealion_board::{Move, Position, PositionKey};
+
+use crate::movegen::Generator;
+use crate::state::PositionState;
+
+/// Count the number of leaf nodes reachable from `position` in exactly `depth` plies.
+pub fn perft(position: &Position, depth: usize) -> usize {
+    if depth == 0 {
+        return 1;
+    }
+
+    let state = PositionState::generate_for_movegen(position);
+    let mut nodes = 0;
+
+    let _ = Generator::new(&state).for_each_move(|p_move| {
+        let mut new_position = position.clone();
+        new_position.apply_move_unchecked(p_move);
+        nodes += perft(&new_position, depth - 1);
+        ControlFlow::Continue(())
+    });
+
+    nodes
+}
+
+/// [`perft`] split up by the moves from `position`, as printed by `go perft` in UCI engines.
+///
+/// Comparing this against a reference engine narrows down which move leads to a wrong count.
+pub fn divide(position: &Position, depth: usize) -> Vec<(Move, usize)> {
+    let mut counts = Vec::new();
+
+    if depth == 0 {
+        return counts;
+    }
+
+    let state = PositionState::generate_for_movegen(position);
+
+    let _ = Generator::new(&state).for_each_move(|p_move| {
+        let mut new_position = position.clone();
+        new_position.apply_move_unchecked(p_move);
+        counts.push((p_move.to_move(), perft(&new_position, depth - 1)));
+        ControlFlow::Continue(())
+    });
+
+    counts
+}
+
+/// Format [`divide`] the way `go perft` does in UCI engines.
+///
+/// Every root move gets a `move: nodes` line, followed by a blank line and the total node count.
+pub fn divide_report(position: &Position, depth: usize) -> String {
+    let counts = divide(position, depth);
+    let total: usize = counts.iter().map(|(_, nodes)| nodes).sum();
+
+    let mut report = String::new();
+    for (p_move, nodes) in counts {
+        report += &format!("{p_move}: {nodes}\n");
+    }
+    report += &format!("\nNodes searched: {total}");
+
+    report
+}
+
+/// Cached node counts of previously visited subtrees.
+#[derive(Debug, Clone, Default)]
+pub struct PerftTable {
+    entries: HashMap<(PositionKey, usize), usize>,
+    hits: usize,
+}
+
+impl PerftTable {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of cached subtrees.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if no subtrees have been cached.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of times a subtree was found in the cache.
+    #[inline]
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Remove all cached subtrees and reset the hit counter.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.hits = 0;
+    }
+}
+
+/// [`perft`], re-using node counts of subtrees reached by transposition.
+pub fn perft_hashed(position: &Position, depth: usize, table: &mut PerftTable) -> usize {
+    // not worth caching leaves
+    if depth <= 1 {
+        return perft(position, depth);
+    }
+
+    if let Some(nodes) = table.entries.get(&(position.clone().into(), depth)) {
+        table.hits += 1;
+        return *nodes;
+    }
+
+    let state = PositionState::generate_for_movegen(position);
+    let mut nodes = 0;
+
+    let _ = Generator::new(&state).for_each_move(|p_move| {
+        let mut new_position = position.clone();
+        new_position.apply_move_unchecked(p_move);
+        nodes += perft_hashed(&new_position, depth - 1, table);
+        ControlFlow::Continue(())
+    });
+
+    table
+        .entries
+        .insert((position.clone().into(), depth), nodes);
+    nodes
+}
+
+/// Distribution of branching factors across the nodes exactly `depth` plies from `position`.
+///
+/// Maps each number of legal moves to the number of nodes with that many moves.
+pub fn perft_branching_histogram(position: &Position, depth: usize) -> BTreeMap<usize, usize> {
+    let mut histogram = BTreeMap::new();
+    branching_histogram_impl(position, depth, &mut histogram);
+    histogram
+}
+
+fn branching_histogram_impl(
+    position: &Position,
+    depth: usize,
+    histogram: &mut BTreeMap<usize, usize>,
+) {
+    let state = PositionState::generate_for_movegen(position);
+    let generator = Generator::new(&state);
+
+    if depth == 0 {
+        let mut count = 0;
+        let _ = generator.for_each_move(|_| {
+            count += 1;
+            ControlFlow::Continue(())
+        });
+
+        *histogram.entry(count).or_default() += 1;
+        return;
+    }
+
+    let _ = generator.for_each_move(|p_move| {
+        let mut new_position = position.clone();
+        new_position.apply_move_unchecked(p_move);
+        branching_histogram_impl(&new_position, depth - 1, histogram);
+        ControlFlow::Continue(())
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::PositionExt;
+
+    fn play(fen: &str, moves: &[&str]) -> Position {
+        let mut position = sealion_fen::from_str(fen).unwrap();
+        for lan in moves {
+            position.apply_uci_move(lan).unwrap();
+        }
+        position
+    }
+
+    #[test]
+    fn hashed_start_pos() {
+        let position = Position::starting();
+        let mut table = PerftTable::new();
+
+        let nodes = perft_hashed(&position, 5, &mut table);
+
+        assert_eq!(nodes, perft(&position, 5));
+        assert_eq!(nodes, 4_865_609);
+        assert!(table.hits() > 0);
+        assert!(!table.is_empty());
+    }
+
+    #[test]
+    fn divide_start_pos() {
+        let position = Position::starting();
+        let counts = divide(&position, 2);
+
+        assert_eq!(counts.len(), 20);
+        assert!(counts.iter().all(|(_, nodes)| *nodes == 20));
+        assert_eq!(counts.iter().map(|(_, nodes)| nodes).sum::<usize>(), 400);
+
+        assert!(divide(&position, 0).is_empty());
+    }
+
+    #[test]
+    fn divide_report_start_pos() {
+        let report = divide_report(&Position::starting(), 2);
+        let mut lines = report.lines();
+
+        for line in lines.by_ref().take(20) {
+            let (p_move, nodes) = line.split_once(": ").unwrap();
+            assert_eq!(p_move.len(), 4);
+            assert_eq!(nodes, "20");
+        }
+        assert!(report.contains("e2e4: 20\n"));
+
+        assert_eq!(lines.next(), Some(""));
+        assert_eq!(lines.next(), Some("Nodes searched: 400"));
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn hashed_ep_transposition() {
+        const ROOT: &str = "4k3/3p4/8/4P3/8/8/8/4K3 w - - 0 1";
+
+        // the same board, but only the double push allows exd6 en passant
+        let ep = play(
+            ROOT,
+            &[
+                "e1e2", "e8d8", "e2e1", "d8e7", "e1e2", "e7e8", "e2e1", "d7d5",
+            ],
+        );
+        let no_ep = play(
+            ROOT,
+            &[
+                "e1e2", "d7d6", "e2e1", "e8d8", "e1e2", "d8e8", "e2e1", "d6d5",
+            ],
+        );
+        assert_eq!(ep.board, no_ep.board);
+        assert!(!ep.same_position(&no_ep));
+
+        let mut table = PerftTable::new();
+        let ep_nodes = perft_hashed(&ep, 3, &mut table);
+        let no_ep_nodes = perft_hashed(&no_ep, 3, &mut table);
+
+        assert_eq!(ep_nodes, perft(&ep, 3));
+        assert_eq!(no_ep_nodes, perft(&no_ep, 3));
+        assert_ne!(ep_nodes, no_ep_nodes);
+    }
+
+    #[test]
+    fn hashed_castling_transposition() {
+        let castling = sealion_fen::from_str("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        // both kings went to the next file and back
+        let no_castling = play(
+            "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+            &["e1d1", "e8d8", "d1e1", "d8e8"],
+        );
+        assert_eq!(castling.board, no_castling.board);
+
+        let mut table = PerftTable::new();
+        let castling_nodes = perft_hashed(&castling, 3, &mut table);
+        let no_castling_nodes = perft_hashed(&no_castling, 3, &mut table);
+
+        assert_eq!(castling_nodes, perft(&castling, 3));
+        assert_eq!(no_castling_nodes, perft(&no_castling, 3));
+        assert_ne!(castling_nodes, no_castling_nodes);
+    }
+
+    #[test]
+    fn branching_histogram() {
+        let position = Position::starting();
+
+        assert_eq!(
+            perft_branching_histogram(&position, 0),
+            BTreeMap::from([(20, 1)])
+        );
+
+        // every node at depth 1 has 20 replies
+        assert_eq!(
+            perft_branching_histogram(&position, 1),
+            BTreeMap::from([(20, 20)])
+        );
+
+        let histogram = perft_branching_histogram(&position, 2);
+        let nodes: usize = histogram.values().sum();
+        let leaves: usize = histogram.iter().map(|(moves, count)| moves * count).sum();
+        assert_eq!(nodes, perft(&position, 2));
+        assert_eq!(leaves, perft(&position, 3));
+    }
+}
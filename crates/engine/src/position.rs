@@ -0,0 +1,384 @@
+//! Convenience queries on a [`Position`].
+
+use std::fmt::Display;
+use std::ops::ControlFlow;
+
+use sealion_board::{BitBoard, Move, MoveExt, Position};
+
+use crate::movegen::{self, Generator, MoveList};
+use crate::state::PositionState;
+
+/// Checks and pins against the king of the side to move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Analysis {
+    /// Pieces giving check.
+    pub checkers: BitBoard,
+    /// Pieces pinned to the king.
+    pub pinned: BitBoard,
+    /// Sliders pinning a piece to the king.
+    pub pinners: BitBoard,
+    /// Number of pieces giving check, 2 for a double check.
+    pub check_count: u8,
+}
+
+impl Analysis {
+    /// Collect the analysis from already generated state.
+    pub fn from_state(state: &PositionState) -> Self {
+        let board = &state.position.board;
+        let friendly = board.get_color_bb(state.position.active_color);
+        let unfriendly = board.get_color_bb(state.position.active_color.opposite());
+
+        let mut analysis = Self::default();
+
+        for square in &state.attacks.checkers.melee {
+            analysis.checkers |= BitBoard::from_square(*square);
+        }
+        // slider rays include the attacker itself
+        for ray in &state.attacks.checkers.sliders {
+            analysis.checkers |= *ray & unfriendly;
+        }
+        for ray in &state.attacks.pinners {
+            analysis.pinners |= *ray & unfriendly;
+            analysis.pinned |= *ray & friendly & !state.board_ext.king_bb;
+        }
+
+        analysis.check_count = analysis.checkers.0.count_ones() as u8;
+        analysis
+    }
+}
+
+/// Error applying a move given in text form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveError {
+    /// The move is not valid long algebraic notation.
+    Parse,
+    /// The move is well-formed, but not legal in the position.
+    Illegal,
+}
+
+impl Display for MoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse => write!(f, "invalid move syntax"),
+            Self::Illegal => write!(f, "illegal move"),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+/// Position queries that require extended state or move generation.
+///
+/// These generate the [`PositionState`] internally, so prefer using it directly when
+/// making multiple queries on the same position.
+pub trait PositionExt {
+    /// Check if the side to move is in check.
+    fn is_check(&self) -> bool;
+
+    /// Check if the side to move has been checkmated.
+    fn is_checkmate(&self) -> bool;
+
+    /// Check if the side to move has no legal moves, but is not in check.
+    fn is_stalemate(&self) -> bool;
+
+    /// Find the checks and pins against the king of the side to move.
+    fn analyze(&self) -> Analysis;
+
+    /// Number of legal moves of the side to move.
+    fn branching_factor(&self) -> usize;
+
+    /// All legal moves of the side to move, empty if checkmated or stalemated.
+    fn legal_moves(&self) -> Vec<Move>;
+
+    /// Check if a legal move of the side to move gives check, including discovered checks.
+    fn gives_check(&self, p_move: &MoveExt) -> bool;
+
+    /// Apply a move given in UCI (long algebraic) notation, e.g. `e2e4` or `e7e8q`.
+    ///
+    /// The move is checked against the legal moves of the position, which is left unchanged
+    /// on error.
+    fn apply_uci_move(&mut self, lan: &str) -> Result<MoveExt, MoveError>;
+
+    /// Apply a move if it is legal, leaving the position unchanged otherwise.
+    fn apply_move(&mut self, p_move: Move) -> Result<MoveExt, MoveError>;
+
+    /// Apply a sequence of moves, e.g. to replay a game.
+    ///
+    /// On error, the index of the first move that isn't legal is returned, and the position is
+    /// left unchanged.
+    fn play_line(&mut self, moves: &[Move]) -> Result<(), (usize, MoveError)>;
+}
+
+impl PositionExt for Position {
+    #[inline]
+    fn is_check(&self) -> bool {
+        let state = PositionState::generate(self);
+        state.attacks.bb & state.board_ext.king_bb != 0
+    }
+
+    #[inline]
+    fn is_checkmate(&self) -> bool {
+        let state = PositionState::generate(self);
+        matches!(MoveList::generate(&state), MoveList::Checkmate)
+    }
+
+    #[inline]
+    fn is_stalemate(&self) -> bool {
+        let state = PositionState::generate(self);
+        matches!(MoveList::generate(&state), MoveList::Stalemate)
+    }
+
+    #[inline]
+    fn analyze(&self) -> Analysis {
+        let state = PositionState::generate(self);
+        Analysis::from_state(&state)
+    }
+
+    #[inline]
+    fn branching_factor(&self) -> usize {
+        let state = PositionState::generate(self);
+        let mut count = 0;
+
+        let _ = Generator::new(&state).for_each_move(|_| {
+            count += 1;
+            ControlFlow::Continue(())
+        });
+
+        count
+    }
+
+    fn legal_moves(&self) -> Vec<Move> {
+        let state = PositionState::generate(self);
+        let mut moves = Vec::new();
+
+        let _ = Generator::new(&state).for_each_move(|p_move| {
+            moves.push(p_move.to_move());
+            ControlFlow::Continue(())
+        });
+
+        moves
+    }
+
+    fn gives_check(&self, p_move: &MoveExt) -> bool {
+        let mut after = self.clone();
+        after.apply_move_unchecked(*p_move);
+
+        let board = &after.board;
+
+        match board.find_king(after.active_color) {
+            Some(king) => {
+                let attackers = movegen::attackers_to(board, king, board.get_full_bb());
+                attackers & board.get_color_bb(self.active_color) != 0
+            }
+            None => false,
+        }
+    }
+
+    fn apply_uci_move(&mut self, lan: &str) -> Result<MoveExt, MoveError> {
+        let p_move: Move = lan.parse().map_err(|_| MoveError::Parse)?;
+        self.apply_move(p_move)
+    }
+
+    fn apply_move(&mut self, p_move: Move) -> Result<MoveExt, MoveError> {
+        let state = PositionState::generate(self);
+        let mut legal = None;
+
+        let _ = Generator::new(&state).for_each_move(|m| {
+            if m.to_move() == p_move {
+                legal = Some(m);
+                return ControlFlow::Break(());
+            }
+            ControlFlow::Continue(())
+        });
+
+        let legal = legal.ok_or(MoveError::Illegal)?;
+        self.apply_move_unchecked(legal);
+        Ok(legal)
+    }
+
+    fn play_line(&mut self, moves: &[Move]) -> Result<(), (usize, MoveError)> {
+        let mut position = self.clone();
+
+        for (i, p_move) in moves.iter().enumerate() {
+            position.apply_move(*p_move).map_err(|e| (i, e))?;
+        }
+
+        *self = position;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use sealion_board::Square;
+
+    use super::*;
+
+    const FOOLS_MATE: &str = "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3";
+    const STALEMATE: &str = "4k3/4P3/4K3/8/8/8/8/8 b - - 0 1";
+
+    #[test]
+    fn fools_mate() {
+        let position = sealion_fen::from_str(FOOLS_MATE).unwrap();
+
+        assert!(position.is_check());
+        assert!(position.is_checkmate());
+        assert!(!position.is_stalemate());
+        assert_eq!(position.branching_factor(), 0);
+        assert!(position.legal_moves().is_empty());
+    }
+
+    #[test]
+    fn stalemate() {
+        let position = sealion_fen::from_str(STALEMATE).unwrap();
+
+        assert!(!position.is_check());
+        assert!(!position.is_checkmate());
+        assert!(position.is_stalemate());
+    }
+
+    #[test]
+    fn double_check() {
+        let position = sealion_fen::from_str("4k3/8/8/8/8/3n4/8/r3K3 w - - 0 1").unwrap();
+        let analysis = position.analyze();
+
+        assert_eq!(analysis.check_count, 2);
+        assert_eq!(
+            analysis.checkers,
+            BitBoard::from_square(Square::from_str("a1").unwrap())
+                | BitBoard::from_square(Square::from_str("d3").unwrap())
+        );
+        assert!(analysis.pinned.is_empty());
+    }
+
+    #[test]
+    fn absolute_pin() {
+        let position = sealion_fen::from_str("4k3/4r3/8/8/8/8/4B3/4K3 w - - 0 1").unwrap();
+        let analysis = position.analyze();
+
+        assert_eq!(analysis.check_count, 0);
+        assert!(analysis.checkers.is_empty());
+        assert_eq!(
+            analysis.pinned,
+            BitBoard::from_square(Square::from_str("e2").unwrap())
+        );
+        assert_eq!(
+            analysis.pinners,
+            BitBoard::from_square(Square::from_str("e7").unwrap())
+        );
+    }
+
+    #[test]
+    fn starting() {
+        let position = Position::starting();
+
+        assert!(!position.is_check());
+        assert!(!position.is_checkmate());
+        assert!(!position.is_stalemate());
+        assert_eq!(position.analyze(), Analysis::default());
+        assert_eq!(position.branching_factor(), 20);
+
+        let moves = position.legal_moves();
+        assert_eq!(moves.len(), 20);
+        assert!(moves.contains(&Move::from_str("e2e4").unwrap()));
+    }
+
+    #[test]
+    fn apply_uci_move() {
+        let mut position = Position::starting();
+
+        assert_eq!(position.apply_uci_move("e2e9"), Err(MoveError::Parse));
+        assert_eq!(position.apply_uci_move("hello"), Err(MoveError::Parse));
+        // well-formed, but the pawn can't move three squares
+        assert_eq!(position.apply_uci_move("e2e5"), Err(MoveError::Illegal));
+        // black's move
+        assert_eq!(position.apply_uci_move("e7e5"), Err(MoveError::Illegal));
+        assert_eq!(position, Position::starting());
+
+        for lan in ["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6", "e1g1"] {
+            let p_move = position.apply_uci_move(lan).unwrap();
+            assert_eq!(p_move.to_move().to_string(), lan);
+        }
+
+        let expected = sealion_fen::from_str(
+            "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQ1RK1 b kq - 5 4",
+        )
+        .unwrap();
+        assert_eq!(position, expected);
+    }
+
+    #[test]
+    fn fifty_move_draw() {
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        let mut position = Position::starting();
+
+        for (i, lan) in shuffle.iter().cycle().take(100).enumerate() {
+            assert!(!position.is_fifty_move_draw());
+            position.apply_uci_move(lan).unwrap();
+            assert_eq!(position.halfmove_clock as usize, i + 1);
+        }
+        assert!(position.is_fifty_move_draw());
+
+        // a pawn move resets the clock
+        let mut position = Position::starting();
+        for lan in shuffle.iter().cycle().take(60) {
+            position.apply_uci_move(lan).unwrap();
+        }
+        position.apply_uci_move("e2e4").unwrap();
+        assert_eq!(position.halfmove_clock, 0);
+
+        let shuffle = ["g8f6", "g1f3", "f6g8", "f3g1"];
+        for lan in shuffle.iter().cycle().take(99) {
+            position.apply_uci_move(lan).unwrap();
+        }
+        assert!(!position.is_fifty_move_draw());
+        position.apply_uci_move("f3g1").unwrap();
+        assert!(position.is_fifty_move_draw());
+    }
+
+    #[test]
+    fn gives_check() {
+        let gives_check = |fen: &str, lan: &str| {
+            let position = sealion_fen::from_str(fen).unwrap();
+            let p_move = position.clone().apply_uci_move(lan).unwrap();
+            position.gives_check(&p_move)
+        };
+
+        // direct
+        assert!(gives_check("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "a1a8"));
+        assert!(!gives_check("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "a1a7"));
+        assert!(gives_check("4k3/8/8/8/4N3/8/8/4K3 w - - 0 1", "e4d6"));
+        // castling rook
+        assert!(gives_check("5k2/8/8/8/8/8/8/4K2R w K - 0 1", "e1g1"));
+        // promotion
+        assert!(gives_check("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8q"));
+        assert!(!gives_check("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8n"));
+        // discovered by moving the knight out of the bishop's way
+        assert!(gives_check("7k/8/8/8/3N4/8/1B6/4K3 w - - 0 1", "d4b5"));
+        // en passant reveals the rook along the rank
+        assert!(gives_check("8/8/8/R2Pp2k/8/8/8/4K3 w - e6 0 1", "d5e6"));
+        assert!(!gives_check("8/8/8/3Pp3/8/8/7k/R3K3 w - e6 0 1", "d5e6"));
+    }
+
+    #[test]
+    fn play_line() {
+        let line = |lans: &[&str]| -> Vec<Move> {
+            lans.iter()
+                .map(|lan| Move::from_str(lan).unwrap())
+                .collect()
+        };
+        let scholars_mate = line(&["e2e4", "e7e5", "f1c4", "b8c6", "d1h5", "g8f6", "h5f7"]);
+
+        let mut position = Position::starting();
+        position.play_line(&scholars_mate).unwrap();
+        assert!(position.is_checkmate());
+
+        // the queen can't jump over the pawn on f7
+        let mut position = Position::starting();
+        let illegal = line(&["e2e4", "e7e5", "d1h5", "h5f8"]);
+        assert_eq!(position.play_line(&illegal), Err((3, MoveError::Illegal)));
+        assert_eq!(position, Position::starting());
+    }
+}
@@ -0,0 +1,27 @@
+//! Commonly used types, for glob importing.
+
+pub use sealion_board::{Color, Move, MoveExt, PieceKind, Position, Square};
+
+pub use crate::movegen::{Generator, MoveList};
+pub use crate::position::{MoveError, PositionExt};
+pub use crate::state::PositionState;
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn in_scope() {
+        use crate::prelude::*;
+
+        let position = Position::starting();
+        let state = PositionState::generate(&position);
+
+        let moves: Vec<MoveExt> = match Generator::new(&state).generate() {
+            MoveList::Moves(moves) => moves,
+            _ => panic!("position is not mate"),
+        };
+        let e4: Move = "e2e4".parse().unwrap();
+
+        assert!(moves.iter().any(|m| m.to_move() == e4));
+        assert!(position.legal_moves().contains(&e4));
+    }
+}
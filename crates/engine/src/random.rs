@@ -0,0 +1,92 @@
+//! Random positions and games, for fuzzing.
+
+use rand::Rng;
+use sealion_board::Position;
+
+use crate::movegen::MoveList;
+use crate::position::PositionExt;
+use crate::state::PositionState;
+
+/// Generate a random legal position.
+///
+/// Both kings are present and not next to each other, and the side not to move is not in
+/// check. Pieces are placed at random otherwise, so this is meant for fuzzing rather than
+/// realistic positions.
+pub fn random_position<R: Rng + ?Sized>(rng: &mut R) -> Position {
+    loop {
+        let position = Position::random(rng);
+
+        let mut opponent = position.clone();
+        opponent.active_color = opponent.active_color.opposite();
+
+        if !opponent.is_check() {
+            return position;
+        }
+    }
+}
+
+/// Play random legal moves from `position` until the game ends or `max_plies` are played.
+pub fn random_playout<R: Rng + ?Sized>(
+    position: &Position,
+    rng: &mut R,
+    max_plies: usize,
+) -> Position {
+    let mut position = position.clone();
+
+    for _ in 0..max_plies {
+        let state = PositionState::generate(&position);
+
+        let p_move = match MoveList::generate(&state) {
+            MoveList::Moves(moves) => moves[rng.gen_range(0..moves.len())],
+            _ => break,
+        };
+
+        position.apply_move_unchecked(p_move);
+    }
+
+    position
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn random_positions() {
+        let mut rng = StdRng::seed_from_u64(0x5ea110);
+
+        for _ in 0..1000 {
+            let position = random_position(&mut rng);
+            let fen = sealion_fen::to_string(&position);
+
+            assert_eq!(sealion_fen::from_str(&fen).unwrap(), position, "{fen}");
+
+            let state = PositionState::generate(&position);
+            if let MoveList::Moves(moves) = MoveList::generate(&state) {
+                assert!(moves
+                    .iter()
+                    .all(|m| position.board.get(m.from).unwrap().color == position.active_color));
+            }
+        }
+    }
+
+    #[test]
+    fn random_playouts() {
+        let mut rng = StdRng::seed_from_u64(0x5ea110);
+
+        for _ in 0..20 {
+            let start = random_position(&mut rng);
+            let position = random_playout(&start, &mut rng, 100);
+            let fen = sealion_fen::to_string(&position);
+
+            // every square holds at most one piece kind and color
+            assert_eq!(sealion_fen::from_str(&fen).unwrap(), position, "{fen}");
+
+            let state = PositionState::generate(&position);
+            let _ = MoveList::generate(&state);
+        }
+    }
+}
@@ -1,11 +1,11 @@
 //! Extended board state.
 
-use sealion_board::{BitBoard, Capture, Piece, PieceKind, Position, Square};
+use sealion_board::{BitBoard, Capture, Color, Piece, PieceKind, Position, Square};
 use smallvec::SmallVec;
 
 use PieceKind::*;
 
-use crate::movegen::{merge_bb, Generator};
+use crate::movegen::{self, merge_bb, Generator};
 
 #[derive(Debug, Clone)]
 pub struct BoardExt {
@@ -31,8 +31,12 @@ impl Default for BoardExt {
 }
 
 /// Pseudo evaluation after an initial run through of the position.
+///
+/// All scores are relative to the side to move, i.e, positive scores are good for the side to
+/// move regardless of its color.
 #[derive(Debug, Clone, Default)]
 pub struct PseudoScore {
+    /// Material balance, see [`PieceKind::score`].
     pub pieces: i16,
     pub position: i16,
     pub attacked: i16,
@@ -53,10 +57,27 @@ pub struct Checkers {
     pub sliders: SmallVec<[BitBoard; 2]>,
 }
 
+impl Checkers {
+    /// Number of pieces giving check.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.melee.len() + self.sliders.len()
+    }
+
+    /// Check if the king is not in check.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.melee.is_empty() && self.sliders.is_empty()
+    }
+}
+
 /// Opponent attacking information.
 #[derive(Debug, Clone, Default)]
 pub struct Attacks {
     /// Attacked squares to restrict king movement.
+    ///
+    /// Sliding attacks pass through the king of the side to move, so squares behind it are
+    /// included as well.
     pub bb: BitBoard,
     /// Attackers on our king.
     pub checkers: Checkers,
@@ -77,6 +98,18 @@ pub struct PositionState<'a> {
 
 impl<'a> PositionState<'a> {
     pub fn generate(position: &'a Position) -> Self {
+        Self::generate_impl::<true>(position)
+    }
+
+    /// Generate only what is needed for move generation, leaving [`score`](Self::score) zeroed.
+    ///
+    /// Prefer this when the moves are all that is needed, e.g. in perft.
+    pub fn generate_for_movegen(position: &'a Position) -> Self {
+        Self::generate_impl::<false>(position)
+    }
+
+    #[inline]
+    fn generate_impl<const SCORE: bool>(position: &'a Position) -> Self {
         let mut this = Self {
             position,
             board_ext: BoardExt::default(),
@@ -94,9 +127,13 @@ impl<'a> PositionState<'a> {
                 this.board_ext.pieces[square.raw_index() as usize] = Some(piece);
 
                 if piece.color == position.active_color {
-                    this.score.pieces += piece.kind.score();
+                    if SCORE {
+                        this.score.pieces += piece.kind.score();
+                    }
                 } else {
-                    this.score.pieces -= piece.kind.score();
+                    if SCORE {
+                        this.score.pieces -= piece.kind.score();
+                    }
                     this.generate_attacks(square, piece.kind);
                 }
 
@@ -187,6 +224,33 @@ impl<'a> PositionState<'a> {
         }
     }
 
+    /// All squares attacked by the pieces of `color`.
+    ///
+    /// Unlike [`Attacks::bb`], this works for either side, and sliding attacks stop at the first
+    /// piece they hit, including kings. The attacks are computed on every call.
+    #[inline]
+    pub fn attacks_for(&self, color: Color) -> BitBoard {
+        movegen::attacks_by(&self.position.board, color)
+    }
+
+    /// Pieces of the side to move that are attacked by the opponent, but not defended.
+    ///
+    /// The king is never included.
+    #[inline]
+    pub fn hanging_pieces(&self) -> BitBoard {
+        let color = self.position.active_color;
+        let attacked = self.attacks_for(!color);
+        let defended = self.attacks_for(color);
+
+        self.position.board.get_color_bb(color) & !self.board_ext.king_bb & attacked & !defended
+    }
+
+    /// Material balance from the point of view of the side to move.
+    #[inline]
+    pub fn material_balance(&self) -> i16 {
+        self.score.pieces
+    }
+
     #[inline]
     pub fn resolve_capture_only(&self, to_sq: Square) -> Option<Capture> {
         if let Some(piece) = self.board_ext.pieces[to_sq.raw_index() as usize] {
@@ -196,10 +260,19 @@ impl<'a> PositionState<'a> {
         None
     }
 
+    /// Square of the pawn that is captured by an en passant move to `ep_target`.
+    #[inline]
+    pub fn ep_captured_square(&self, ep_target: Square) -> Square {
+        match self.position.active_color {
+            Color::White => Square::from_index_unchecked(ep_target.raw_index() - 8),
+            Color::Black => Square::from_index_unchecked(ep_target.raw_index() + 8),
+        }
+    }
+
     #[inline]
     pub fn resolve_ep(&self, to_sq: Square) -> Option<Capture> {
         if Some(to_sq) == self.position.ep_target {
-            return Some(Capture::EnPassant);
+            return Some(Capture::EnPassant(self.ep_captured_square(to_sq)));
         }
 
         None
@@ -211,3 +284,124 @@ impl<'a> PositionState<'a> {
             .or_else(|| self.resolve_ep(to_sq))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::movegen::MoveList;
+
+    #[test]
+    fn material_balance() {
+        // white is up a rook
+        let white = sealion_fen::from_str("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        assert_eq!(PositionState::generate(&white).material_balance(), 500);
+
+        let black = sealion_fen::from_str("4k3/8/8/8/8/8/8/R3K3 b - - 0 1").unwrap();
+        assert_eq!(PositionState::generate(&black).material_balance(), -500);
+
+        let start = Position::starting();
+        assert_eq!(PositionState::generate(&start).material_balance(), 0);
+    }
+
+    #[test]
+    fn attacks_for() {
+        let position = Position::starting();
+        let state = PositionState::generate(&position);
+
+        let white = state.attacks_for(Color::White);
+        let black = state.attacks_for(Color::Black);
+
+        // ranks 2 and 3 are attacked, as well as everything on rank 1 except the corners
+        assert_eq!(white, 0x0000_0000_00ff_ff7e);
+        // the start position is symmetric
+        assert_eq!(black, white.flip_vertical());
+    }
+
+    #[test]
+    fn hanging_pieces() {
+        // the knight on c3 is defended by the b2 pawn, the one on f5 is not
+        let position = sealion_fen::from_str("4k3/8/4p3/b4N2/8/2N5/1P6/4K3 w - - 0 1").unwrap();
+        let state = PositionState::generate(&position);
+
+        assert_eq!(
+            state.hanging_pieces(),
+            BitBoard::from_square(Square::from_str("f5").unwrap())
+        );
+    }
+
+    #[test]
+    fn generate_for_movegen() {
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3",
+            "4k3/4P3/4K3/8/8/8/8/8 b - - 0 1",
+        ];
+
+        for fen in fens {
+            let position = sealion_fen::from_str(fen).unwrap();
+            let full = PositionState::generate(&position);
+            let movegen = PositionState::generate_for_movegen(&position);
+
+            assert_eq!(
+                MoveList::generate(&full).to_string(),
+                MoveList::generate(&movegen).to_string()
+            );
+            assert_eq!(movegen.material_balance(), 0);
+        }
+    }
+
+    #[test]
+    fn cached_board() {
+        let position = sealion_fen::from_str(
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1",
+        )
+        .unwrap();
+        let state = PositionState::generate(&position);
+        let board = &position.board;
+
+        assert_eq!(state.board_ext.pieces, board.placement());
+        assert_eq!(
+            state.board_ext.king_bb,
+            board.get_piece_bb(Piece {
+                color: Color::Black,
+                kind: PieceKind::King
+            })
+        );
+    }
+
+    #[test]
+    fn black_ep_capture() {
+        let mut position =
+            sealion_fen::from_str("rnbqkbnr/ppp1pppp/8/8/2PpP3/8/PP1P1PPP/RNBQKBNR b KQkq e3 0 3")
+                .unwrap();
+        let state = PositionState::generate(&position);
+
+        let from = Square::from_str("d4").unwrap();
+        let to = Square::from_str("e3").unwrap();
+        let captured = Square::from_str("e4").unwrap();
+
+        assert!(matches!(
+            state.resolve_ep(to),
+            Some(Capture::EnPassant(sq)) if sq == captured
+        ));
+
+        let p_move = match MoveList::generate(&state) {
+            MoveList::Moves(moves) => moves
+                .into_iter()
+                .find(|m| m.from == from && m.to == to)
+                .expect("ep capture should be legal"),
+            _ => panic!("position is not mate"),
+        };
+
+        position.apply_move_unchecked(p_move);
+
+        // only the e4 pawn is captured, c4 is untouched
+        let expected =
+            sealion_fen::from_str("rnbqkbnr/ppp1pppp/8/8/2P5/4p3/PP1P1PPP/RNBQKBNR w KQkq - 0 1")
+                .unwrap();
+        sealion_board::assert_position_eq!(position, expected);
+    }
+}
@@ -0,0 +1,198 @@
+//! Time management, deciding how long to search the current move for.
+//!
+//! <https://www.chessprogramming.org/Time_Management>
+
+use std::time::{Duration, Instant};
+
+use sealion_board::Color;
+
+/// Time reserved for communication delays between the engine and the GUI.
+const MOVE_OVERHEAD: Duration = Duration::from_millis(50);
+
+/// Moves to plan for when the time control doesn't specify it.
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+
+/// Source of monotonic time, so that tests can control it.
+pub trait Clock {
+    /// Time elapsed since some fixed point.
+    fn now(&self) -> Duration;
+}
+
+/// Wall-clock time.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    epoch: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self {
+            epoch: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    #[inline]
+    fn now(&self) -> Duration {
+        self.epoch.elapsed()
+    }
+}
+
+/// Clock state for both players, as sent with the UCI `go` command.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GoParams {
+    /// Time left on white's clock.
+    pub wtime: Option<Duration>,
+    /// Time left on black's clock.
+    pub btime: Option<Duration>,
+    /// White's increment per move.
+    pub winc: Duration,
+    /// Black's increment per move.
+    pub binc: Duration,
+    /// Moves until the next time control.
+    pub movestogo: Option<u32>,
+}
+
+/// Search time limits for the current move.
+///
+/// Iterative deepening should not start a new iteration after the soft limit, and should abort
+/// an ongoing search at the hard limit.
+#[derive(Debug, Clone)]
+pub struct TimeManager<C = SystemClock> {
+    clock: C,
+    start: Duration,
+    soft_limit: Duration,
+    hard_limit: Duration,
+}
+
+impl TimeManager {
+    /// Allocate time for `color` to move, using the wall clock.
+    #[inline]
+    pub fn new(params: &GoParams, color: Color) -> Self {
+        Self::with_clock(params, color, SystemClock::default())
+    }
+}
+
+impl<C: Clock> TimeManager<C> {
+    /// Allocate time for `color` to move, starting now.
+    ///
+    /// Without time left on the clock of `color`, the search is unlimited.
+    pub fn with_clock(params: &GoParams, color: Color, clock: C) -> Self {
+        let (time, inc) = match color {
+            Color::White => (params.wtime, params.winc),
+            Color::Black => (params.btime, params.binc),
+        };
+
+        let (soft_limit, hard_limit) = match time {
+            Some(time) => {
+                let moves = params.movestogo.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+                let max = time.saturating_sub(MOVE_OVERHEAD);
+
+                let base = time / moves + inc * 3 / 4;
+                (base.min(max), (base * 3).min(max))
+            }
+            None => (Duration::MAX, Duration::MAX),
+        };
+
+        Self {
+            start: clock.now(),
+            clock,
+            soft_limit,
+            hard_limit,
+        }
+    }
+
+    #[inline]
+    pub fn soft_limit(&self) -> Duration {
+        self.soft_limit
+    }
+
+    #[inline]
+    pub fn hard_limit(&self) -> Duration {
+        self.hard_limit
+    }
+
+    /// Time spent on this move so far.
+    #[inline]
+    pub fn elapsed(&self) -> Duration {
+        self.clock.now().saturating_sub(self.start)
+    }
+
+    /// Check if there is time to start another iteration.
+    #[inline]
+    pub fn can_start_iteration(&self) -> bool {
+        self.elapsed() < self.soft_limit
+    }
+
+    /// Check if the search has to be aborted.
+    #[inline]
+    pub fn should_abort(&self) -> bool {
+        self.elapsed() >= self.hard_limit
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct FakeClock(Cell<Duration>);
+
+    impl Clock for &FakeClock {
+        fn now(&self) -> Duration {
+            self.0.get()
+        }
+    }
+
+    fn params(movestogo: Option<u32>) -> GoParams {
+        GoParams {
+            wtime: Some(Duration::from_secs(60)),
+            btime: Some(Duration::from_secs(10)),
+            movestogo,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn movestogo() {
+        let clock = FakeClock::default();
+        let soft_limit = |movestogo| {
+            TimeManager::with_clock(&params(Some(movestogo)), Color::White, &clock).soft_limit()
+        };
+
+        // less time per move the more moves have to be played with it
+        assert!(soft_limit(40) < soft_limit(20));
+        assert!(soft_limit(20) < soft_limit(10));
+        assert!(soft_limit(1) < Duration::from_secs(60));
+    }
+
+    #[test]
+    fn limits() {
+        let clock = FakeClock::default();
+        clock.0.set(Duration::from_secs(100));
+
+        let manager = TimeManager::with_clock(&params(None), Color::Black, &clock);
+        assert!(manager.soft_limit() < manager.hard_limit());
+        assert!(manager.hard_limit() < Duration::from_secs(10));
+
+        assert!(manager.can_start_iteration());
+        assert!(!manager.should_abort());
+
+        clock.0.set(Duration::from_secs(100) + manager.soft_limit());
+        assert!(!manager.can_start_iteration());
+        assert!(!manager.should_abort());
+
+        clock.0.set(Duration::from_secs(100) + manager.hard_limit());
+        assert!(manager.should_abort());
+    }
+
+    #[test]
+    fn infinite() {
+        let manager = TimeManager::new(&GoParams::default(), Color::White);
+        assert!(manager.can_start_iteration());
+        assert!(!manager.should_abort());
+    }
+}
@@ -0,0 +1,51 @@
+//! WebAssembly bindings.
+//!
+//! Thin wrappers for running the engine in a browser, without going through UCI.
+
+use sealion_board::Position;
+use wasm_bindgen::prelude::*;
+
+use crate::movegen::MoveList;
+use crate::state::PositionState;
+
+/// All legal moves in the position, in [LAN].
+///
+/// [LAN]: https://www.chessprogramming.org/Algebraic_Chess_Notation#Long_Algebraic_Notation_.28LAN.29
+pub fn lan_moves(position: &Position) -> Vec<String> {
+    let state = PositionState::generate(position);
+
+    match MoveList::generate(&state) {
+        MoveList::Moves(moves) => moves.iter().map(|m| m.to_move().to_string()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// All legal moves in the position described by `fen`, in LAN.
+#[wasm_bindgen]
+pub fn legal_moves(fen: &str) -> Result<Vec<String>, JsError> {
+    let position = sealion_fen::from_str(fen).map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(lan_moves(&position))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn starting() {
+        let moves = lan_moves(&Position::starting());
+
+        assert_eq!(moves.len(), 20);
+        assert!(moves.iter().any(|m| m == "e2e4"));
+        assert!(moves.iter().any(|m| m == "g1f3"));
+    }
+
+    #[test]
+    fn checkmate() {
+        let position =
+            sealion_fen::from_str("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3")
+                .unwrap();
+
+        assert!(lan_moves(&position).is_empty());
+    }
+}
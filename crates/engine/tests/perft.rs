@@ -1,39 +1,18 @@
 use paste::paste;
 
-use sealion_board::Position;
-use sealion_engine::movegen::MoveList;
-use sealion_engine::state::PositionState;
+use sealion_engine::perft;
 
-pub fn perft(position: &Position, depth: usize, debug_depth: usize) -> usize {
-    if depth == 0 {
-        return 1;
-    }
-
-    let mut nodes = 0;
-
-    let state = PositionState::generate(&position);
-
-    if let MoveList::Moves(moves) = MoveList::generate(&state) {
-        for p_move in moves.into_iter() {
-            let mut new_position = position.clone();
-            new_position.apply_move_unchecked(p_move);
-            let move_nodes = perft(&new_position, depth - 1, debug_depth);
-
-            if depth == debug_depth {
-                println!("{}: {}", p_move.to_move(), move_nodes);
-            }
+/// Check the node count at `depth`, printing the nodes per root move on a mismatch.
+fn do_perft(fen: &str, depth: usize, result: usize) {
+    let position = sealion_fen::from_str(fen).unwrap();
+    let nodes = perft::perft(&position, depth);
 
-            nodes += move_nodes;
-        }
+    if nodes != result {
+        panic!(
+            "`{fen}` at depth {depth}: expected {result} nodes, got {nodes}\n{}",
+            perft::divide_report(&position, depth)
+        );
     }
-
-    nodes
-}
-
-fn do_perft(fen: &str, x: usize, result: usize) {
-    let position = sealion_fen::from_str(fen).unwrap();
-    let nodes = perft(&position, x, x);
-    assert_eq!(nodes, result);
 }
 
 macro_rules! def_test {
@@ -85,3 +64,45 @@ def_test! {
         4 => 2_103_487
     ]
 }
+
+// ====================== EN PASSANT CASES ======================
+
+def_test! {
+    // exd3 would expose the king to the queen along the rank
+    ep_rank_pin "8/8/8/8/k2Pp2Q/8/8/3K4 b - d3 0 1" => [
+        1 => 6,
+        2 => 136,
+        3 => 863,
+        4 => 20_471
+    ]
+}
+
+def_test! {
+    // exd3 captures the checking pawn
+    ep_evades_check "8/8/8/2k5/3Pp3/8/8/3K4 b - d3 0 1" => [
+        1 => 9,
+        2 => 49,
+        3 => 364,
+        4 => 2_233
+    ]
+}
+
+def_test! {
+    // cxd6 would expose the king to the bishop along the diagonal
+    ep_diagonal_pin "8/5bk1/8/2Pp4/8/1K6/8/8 w - d6 0 1" => [
+        1 => 8,
+        2 => 104,
+        3 => 736,
+        4 => 9_287
+    ]
+}
+
+def_test! {
+    // cxd3 discovers a check from the bishop
+    ep_discovered_check "8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1" => [
+        1 => 15,
+        2 => 126,
+        3 => 1_928,
+        4 => 13_931
+    ]
+}
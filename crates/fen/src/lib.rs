@@ -1,11 +1,6 @@
 //! Fen de/serialization utilities.
+//!
+//! The implementation lives in [`sealion_board::fen`], this crate re-exports it for
+//! users that don't need the inherent [`Position`](sealion_board::Position) methods.
 
-use sealion_board::Position;
-
-pub mod de;
-
-/// Parse a position from the given fen string.
-#[inline]
-pub fn from_str(s: &str) -> Result<Position, nom::Err<nom::error::Error<&str>>> {
-    de::parse(s).map(|r| r.1)
-}
+pub use sealion_board::fen::*;
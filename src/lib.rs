@@ -0,0 +1,3 @@
+//! Command line frontend of the sealion engine.
+
+pub mod repl;
@@ -0,0 +1,77 @@
+//! Interactive move listing.
+
+use std::io::{self, BufRead, Write};
+
+use sealion_engine::prelude::*;
+
+/// Read a position from `input` and write its legal moves to `output`.
+///
+/// Invalid positions are reported, and another one is asked for until a valid position is read
+/// or the input ends.
+pub fn run(mut input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    let mut fen = String::new();
+
+    loop {
+        writeln!(output, "Position fen: ")?;
+        output.flush()?;
+
+        fen.clear();
+        if input.read_line(&mut fen)? == 0 {
+            return Ok(());
+        }
+
+        let position = match sealion_fen::from_str(&fen) {
+            Ok(position) => position,
+            Err(e) => {
+                writeln!(output, "Invalid fen: {e}")?;
+                continue;
+            }
+        };
+
+        let state = PositionState::generate(&position);
+        let generator = match Generator::try_new(&state) {
+            Some(generator) => generator,
+            None => {
+                writeln!(
+                    output,
+                    "Illegal position: {} must have exactly one king",
+                    position.active_color
+                )?;
+                continue;
+            }
+        };
+
+        return writeln!(output, "{}", generator.generate());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn run_str(input: &str) -> String {
+        let mut output = Vec::new();
+        run(Cursor::new(input), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn start_pos() {
+        let output = run_str("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1\n");
+        assert!(output.contains("20 legal moves: "));
+    }
+
+    #[test]
+    fn invalid_fen() {
+        let output = run_str("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1\n");
+        assert!(output.contains("Invalid fen: invalid fen syntax at offset 44"));
+
+        // asks again after an invalid position
+        let output = run_str("8/8/8/8/8/8/8/7k w - - 0 1\n8/8/8/8/8/8/8/K6k w - - 0 1\n");
+        assert!(output.contains("Illegal position: white must have exactly one king"));
+        assert!(output.contains("3 legal moves: "));
+        assert_eq!(output.matches("Position fen: ").count(), 2);
+    }
+}